@@ -0,0 +1,62 @@
+use crate::task::Task;
+
+/// Branch name template used by `rudden start` when none is given.
+pub const DEFAULT_TEMPLATE: &str = "task/{id}-{slug}";
+/// Longest slug we put in a branch name, so long task names stay readable.
+const MAX_SLUG_LEN: usize = 40;
+
+/// Turns a task name into a lowercase, dash-separated branch component.
+pub fn slugify(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    if slug.len() > MAX_SLUG_LEN {
+        slug.truncate(MAX_SLUG_LEN);
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+/// Expands `{id}` and `{slug}` in `template` for `task`.
+pub fn branch_name(template: &str, task: &Task) -> String {
+    template
+        .replace("{id}", &task.id.to_string())
+        .replace("{slug}", &slugify(&task.name))
+}
+
+/// Recovers the task ID from a branch created with `template`, if it matches.
+pub fn task_id(template: &str, branch: &str) -> Option<u32> {
+    let (prefix, _) = template.split_once("{id}")?;
+    let rest = branch.strip_prefix(prefix)?;
+    let digits: String = rest.chars().take_while(char::is_ascii_digit).collect();
+    digits.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::{Importance, Status};
+
+    #[test]
+    fn test_slugify_collapses_punctuation() {
+        assert_eq!(slugify("feat: Parse the CONFIG file!"), "feat-parse-the-config-file");
+    }
+
+    #[test]
+    fn test_branch_name_and_task_id_round_trip() {
+        // Arrange
+        let task = Task { id: 12, name: "Short slug".into(), status: Status::Pending, importance: Importance::Normal, ..Default::default() };
+
+        // Act
+        let branch = branch_name(DEFAULT_TEMPLATE, &task);
+
+        // Assert
+        assert_eq!(branch, "task/12-short-slug");
+        assert_eq!(task_id(DEFAULT_TEMPLATE, &branch), Some(12));
+        assert_eq!(task_id(DEFAULT_TEMPLATE, "feature/12-other"), None);
+    }
+}
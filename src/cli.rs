@@ -1,46 +1,373 @@
+use std::path::PathBuf;
+
 use clap::{Args, Parser, Subcommand};
 
+use crate::completions::CompletionShell;
+use crate::export::ExportFormat;
+use crate::import::ImportFormat;
+use crate::query;
+use crate::render::Format;
+use crate::select::TaskSelector;
+use crate::serve;
+use crate::sort::{GroupBy, SortKey};
+use crate::store;
+
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 pub struct Cli {
     #[command(subcommand)]
     pub mode: Mode,
+    /// Use this store directory instead of discovering the nearest `.rudden`.
+    #[arg(long, global = true, env = store::STORE_ENV)]
+    pub store: Option<PathBuf>,
+    /// Use your personal task list instead of the repository's.
+    #[arg(long, global = true, conflicts_with = "store")]
+    pub global: bool,
+    /// How to print command results: human-readable text, or json, ndjson or csv for scripts.
+    #[arg(long, global = true, value_enum, default_value_t = Format::Text)]
+    pub format: Format,
+    /// Print only the IDs of the tasks a command touched, one per line.
+    #[arg(long, short, global = true, conflicts_with = "format")]
+    pub quiet: bool,
 }
 
 #[derive(Subcommand, Debug)]
 pub enum Mode {
-    Check,
+    /// Creates the `.rudden` store at the repository root (or the current directory).
+    Init,
+    /// Reads the git log to start tasks whose branches have commits and finish tasks named by
+    /// commits, closed by a `Closes #<id>` line or whose branches were merged.
+    Check(CheckArgs),
+    /// Adds a task.
     Add(AddArgs),
+    /// Changes the status, importance, tags, parent or description of one or more tasks.
     Update(UpdateArgs),
+    /// Removes one or more tasks.
     Rm(RmArgs),
-    Show,
+    /// Lists the tasks, optionally filtered by a query, sorted, paged or grouped.
+    Show(ShowArgs),
+    /// Compares rewriting the task file with appending to it, using a scratch file in the current directory.
     Bench(BenchArgs),
+    /// Installs, removes or reports on the git hooks that keep tasks in step with commits.
+    Hooks(HooksArgs),
+    /// Prints a commit message that finishes the given tasks when `check` reads it.
+    CommitMsg(CommitMsgArgs),
+    /// Marks a task as started and checks out a branch for it.
+    Start(StartArgs),
+    /// Summarizes every registered repository's tasks.
+    Dash(DashArgs),
+    /// Reads and writes configuration keys.
+    Config(ConfigArgs),
+    /// Finds tasks by words in their names, descriptions and tags, best matches first.
+    Search(SearchArgs),
+    /// Moves finished tasks out of the task list into the store's archive.
+    Archive(ArchiveArgs),
+    /// Moves archived tasks back into the task list.
+    Unarchive(UnarchiveArgs),
+    /// Adds the tasks from a todo.txt, Markdown, GitHub issues or CSV file, skipping any already in the list.
+    Import(ImportArgs),
+    /// Prints the tasks as a todo.txt file, a Markdown checklist, an HTML page or an iCalendar file.
+    Export(ExportArgs),
+    /// Turns TODO, FIXME and HACK comments into tasks, and finishes them when the comments go.
+    Scan(ScanArgs),
+    /// Opens a full-screen task list to browse, filter and edit tasks with the keyboard.
+    Tui,
+    /// Runs rudden commands one after another against a list loaded once, with history and
+    /// tab completion.
+    Shell,
+    /// Prints a completion script for bash, zsh, fish or elvish, e.g. `rudden completions bash > /etc/bash_completion.d/rudden`.
+    Completions(CompletionsArgs),
+    /// Writes the command-line reference in Markdown and a man page for each command.
+    GenDocs(GenDocsArgs),
+    /// Serves the tasks as a JSON API over HTTP, for web boards and editor integrations.
+    Serve(ServeArgs),
+    /// Lists the task IDs with their names, for completion scripts.
+    #[command(name = "__complete", hide = true)]
+    Complete,
+}
+
+#[derive(Args, Debug, Default)]
+pub struct ShowArgs {
+    /// Only show tasks matching this query, e.g. `status:pending importance>=important tag:api`
+    /// or a saved `@name`.
+    pub query: Vec<String>,
+    /// Show the repository and personal lists together.
+    #[arg(long)]
+    pub all: bool,
+    /// Show archived tasks instead of the task list.
+    #[arg(long, conflicts_with = "all")]
+    pub archived: bool,
+    /// Print an aligned table instead of one line per task. Overrides `show.style`.
+    #[arg(long)]
+    pub table: bool,
+    /// Table columns, comma-separated: id, name, status, importance, age, tags. Implies `--table`.
+    #[arg(long)]
+    pub columns: Option<String>,
+    /// Sort keys, comma-separated: id, name, status, importance, created. Prefix a key with `-`
+    /// to reverse it, e.g. `-importance,created`.
+    #[arg(long, value_delimiter = ',', allow_hyphen_values = true)]
+    pub sort: Vec<SortKey>,
+    /// Print tasks in sections, each with a header and count.
+    #[arg(long, value_enum)]
+    pub group_by: Option<GroupBy>,
+    /// Show at most this many tasks.
+    #[arg(long)]
+    pub limit: Option<usize>,
+    /// Skip this many tasks, after sorting.
+    #[arg(long, default_value_t = 0)]
+    pub offset: usize,
+}
+
+#[derive(Args, Debug, Default)]
+pub struct SearchArgs {
+    /// Words to look for; every word must match. Case and accents are ignored.
+    #[arg(required = true)]
+    pub terms: Vec<String>,
+    /// Also match words with a typo or two.
+    #[arg(long)]
+    pub fuzzy: bool,
+}
+
+#[derive(Args, Debug, Default)]
+pub struct ArchiveArgs {
+    /// Only archive tasks finished at least this long ago, e.g. `14d` or `2w`.
+    #[arg(long, value_parser = parse_age)]
+    pub older_than: Option<u64>,
+}
+
+#[derive(Args, Debug, Default)]
+pub struct UnarchiveArgs {
+    /// The archived tasks: IDs, `#ID`s, ranges like `3-9`, `last`, or parts of their names.
+    #[arg(required = true)]
+    pub tasks: Vec<TaskSelector>,
+}
+
+#[derive(Args, Debug)]
+pub struct ImportArgs {
+    /// The file to import, or `-` to read standard input.
+    pub file: PathBuf,
+    /// The file's format.
+    #[arg(long, value_enum)]
+    pub from: ImportFormat,
+}
+
+#[derive(Args, Debug)]
+pub struct ExportArgs {
+    /// The format to write.
+    #[arg(long, value_enum)]
+    pub to: ExportFormat,
+    /// Only export tasks matching this query, as for `show`.
+    pub query: Vec<String>,
+}
+
+#[derive(Args, Debug, Default)]
+pub struct ScanArgs {
+    /// Files or directories to scan instead of the whole repository.
+    pub paths: Vec<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct CompletionsArgs {
+    /// The shell to complete for.
+    #[arg(value_enum)]
+    pub shell: CompletionShell,
+}
+
+#[derive(Args, Debug)]
+pub struct GenDocsArgs {
+    /// Directory to write `cli.md` and the `man` pages into.
+    #[arg(long, default_value = "docs")]
+    pub out_dir: PathBuf,
+}
+
+#[derive(Args, Debug)]
+pub struct ServeArgs {
+    /// Port to listen on; 0 picks a free one.
+    #[arg(long, default_value_t = 7420)]
+    pub port: u16,
+    /// Address to listen on. Anything but localhost lets other machines reach the tasks, so
+    /// set a token too.
+    #[arg(long, default_value = "127.0.0.1")]
+    pub host: String,
+    /// Require every request to send `Authorization: Bearer <TOKEN>`.
+    #[arg(long, env = serve::TOKEN_ENV, hide_env_values = true)]
+    pub token: Option<String>,
+}
+
+fn parse_age(value: &str) -> Result<u64, String> {
+    query::parse_duration(value).ok_or_else(|| format!("'{}' is not a valid duration. Use e.g. 30m, 12h, 7d or 2w.", value))
 }
 
 #[derive(Args, Debug)]
 pub struct BenchArgs {
+    /// How many tasks to add with each method.
     #[arg(long, default_value = "1000")]
     pub tasks: u32,
 }
 
-#[derive(Args, Debug)]
+#[derive(Args, Debug, Default)]
 pub struct AddArgs {
+    /// The task's name.
     #[arg(short, long)]
     pub message: String,
+    /// normal, important or urgent. Defaults to `add.default_importance`.
     #[arg(short, long)]
     pub importance: Option<String>,
+    /// Tag the task; repeat for several tags.
+    #[arg(short, long = "tag")]
+    pub tags: Vec<String>,
+    /// Make the task a subtask of this task.
+    #[arg(long)]
+    pub parent: Option<u32>,
+    /// Longer notes about the task.
+    #[arg(short, long)]
+    pub description: Option<String>,
 }
 
-#[derive(Args, Debug)]
+#[derive(Args, Debug, Default)]
 pub struct UpdateArgs {
-    pub id: u32,
+    /// The tasks: IDs, `#ID`s, ranges like `3-9`, `last`, or parts of their names.
+    #[arg(required_unless_present = "filter")]
+    pub tasks: Vec<TaskSelector>,
+    /// Also update every task matching this query, e.g. `status:started tag:api`.
+    #[arg(long = "where", value_name = "QUERY")]
+    pub filter: Option<String>,
+    /// The new status: pending, started or finished.
     #[arg(short, long)]
     pub status: Option<String>,
+    /// The new importance: normal, important or urgent.
     #[arg(short, long)]
     pub importance: Option<String>,
+    /// Add a tag; repeat for several tags.
+    #[arg(short, long = "tag")]
+    pub tags: Vec<String>,
+    /// Remove a tag; repeat for several tags.
+    #[arg(long = "untag")]
+    pub untags: Vec<String>,
+    /// Make the task a subtask of this task.
+    #[arg(long, conflicts_with = "no_parent")]
+    pub parent: Option<u32>,
+    /// Make the task a top-level task again.
+    #[arg(long)]
+    pub no_parent: bool,
+    /// Replace the task's description; an empty one removes it.
+    #[arg(short, long)]
+    pub description: Option<String>,
 }
 
-#[derive(Args, Debug)]
+#[derive(Args, Debug, Default)]
 pub struct RmArgs {
-    pub id: u32,
+    /// The tasks: IDs, `#ID`s, ranges like `3-9`, `last`, or parts of their names.
+    #[arg(required_unless_present = "filter")]
+    pub tasks: Vec<TaskSelector>,
+    /// Also remove every task matching this query, e.g. `status:finished`.
+    #[arg(long = "where", value_name = "QUERY")]
+    pub filter: Option<String>,
+    /// Remove more tasks at once than `rm.confirm_above` allows.
+    #[arg(long, short)]
+    pub yes: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct HooksArgs {
+    /// What to do with the hooks.
+    #[command(subcommand)]
+    pub action: HooksAction,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum HooksAction {
+    /// Installs rudden's post-commit, post-merge, commit-msg and prepare-commit-msg hooks,
+    /// chaining to any hook already there.
+    Install,
+    /// Removes rudden's hooks and puts back the ones they were chained to.
+    Uninstall,
+    /// Shows which hooks are installed.
+    Status,
+    /// Warns about unknown task IDs referenced by a commit message (used by the commit-msg hook).
+    #[command(hide = true)]
+    CheckMsg {
+        /// The commit message file git passes to the hook.
+        file: PathBuf,
+    },
+    /// Pre-fills a commit message with the branch's or started tasks (used by the prepare-commit-msg hook).
+    #[command(hide = true)]
+    PrepareMsg {
+        /// The commit message file git passes to the hook.
+        file: PathBuf,
+        /// Where the message came from, as git passes it: `message`, `template`, `merge` and so on.
+        source: Option<String>,
+    },
+}
+
+#[derive(Args, Debug)]
+pub struct CommitMsgArgs {
+    /// The tasks: IDs, `#ID`s, `last`, or parts of their names.
+    #[arg(required = true)]
+    pub tasks: Vec<TaskSelector>,
+}
+
+#[derive(Args, Debug, Default)]
+pub struct BranchArgs {
+    /// Branch name template; `{id}` and `{slug}` are replaced from the task. Overrides `branch.template`.
+    #[arg(long)]
+    pub branch_template: Option<String>,
+    /// Branch that task branches are merged into. Overrides `branch.main`.
+    #[arg(long)]
+    pub main_branch: Option<String>,
+}
+
+#[derive(Args, Debug, Default)]
+pub struct CheckArgs {
+    #[command(flatten)]
+    pub branch: BranchArgs,
+    /// Do nothing, rather than fail, when there is no store (used by the post-commit and post-merge hooks).
+    #[arg(long, hide = true)]
+    pub skip_without_store: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct StartArgs {
+    /// The task: an ID, `#ID`, `last`, or part of its name.
+    pub task: TaskSelector,
+    /// Only mark the task as started, without touching git branches.
+    #[arg(long)]
+    pub no_branch: bool,
+    #[command(flatten)]
+    pub branch: BranchArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct DashArgs {
+    /// How many open tasks to list across all repositories.
+    #[arg(long, default_value = "10")]
+    pub top: usize,
+}
+
+#[derive(Args, Debug)]
+pub struct ConfigArgs {
+    /// What to do with the configuration.
+    #[command(subcommand)]
+    pub action: ConfigAction,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigAction {
+    /// Prints the effective value of a key.
+    Get {
+        /// The key, e.g. `show.style`.
+        key: String,
+    },
+    /// Writes a key to the repository's `.rudden/config.toml`.
+    Set {
+        /// The key, e.g. `show.style`.
+        key: String,
+        /// The value to write.
+        value: String,
+        /// Write to the user config file instead.
+        #[arg(long)]
+        user: bool,
+    },
+    /// Lists every key with its effective value and where it came from.
+    List,
 }
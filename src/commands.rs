@@ -1,135 +1,592 @@
-use crate::cli::{AddArgs, RmArgs, UpdateArgs, BenchArgs};
-use crate::task::{Importance, Status, ToDoList};
+use crate::cli::{AddArgs, RmArgs, UpdateArgs, BenchArgs, CommitMsgArgs, ConfigAction, ConfigArgs, DashArgs, HooksAction, HooksArgs, SearchArgs, ShowArgs, StartArgs, ArchiveArgs, UnarchiveArgs, ImportArgs, ExportArgs, ScanArgs, GenDocsArgs};
+use crate::config::{self, Config, ConfigError, Source};
+use crate::error::{Error, Result};
+use crate::outcome::{
+    AddOutcome, ArchiveOutcome, BenchOutcome, CheckOutcome, CommitMsgOutcome, ConfigEntry, ConfigOutcome, DashOutcome, DashTask, DocsOutcome, ExportOutcome,
+    HookStatus, HooksOutcome, ImportOutcome, InitOutcome, ListedTask, Origin, RemoveOutcome, RepoSummary, ScanOutcome, SearchOutcome, ShowOutcome,
+    StartOutcome, TaskRef, UnarchiveOutcome, UpdateOutcome,
+};
+use crate::task::{self, Importance, Status, Task, TaskId, ToDoList};
 use crate::persistence;
+use crate::query::Query;
+use crate::git::{self, BranchEvent};
+use crate::store::Store;
+use crate::{docs, export};
+use crate::import::{self, ImportedTask};
+use crate::{branch, hooks, scan, search, select, sort};
 use std::fs;
 use std::io;
 use std::path::Path;
 use std::time::Instant;
 
+/// Creates the store directory so other commands can save into it.
+pub fn init_store(store: &Store) -> Result<InitOutcome> {
+    let created = !store.exists();
+    if created {
+        fs::create_dir_all(&store.dir)?;
+    }
+    Ok(InitOutcome { dir: store.dir.clone(), created })
+}
+
 /// Adds a new task to the list. Takes a reference to AddArgs.
-pub fn add_task(to_do_list: &mut ToDoList, args: &AddArgs) -> Result<String, String> {
-    let importance_str = args.importance.as_deref().unwrap_or("normal");
-    let importance = match importance_str.parse::<Importance>() {
-        Ok(imp) => imp,
-        Err(_) => return Err(format!("'{}' is not a valid importance. Use 'normal', 'important', or 'urgent'.", importance_str)),
+/// Without `--importance`, the configured `add.default_importance` is used.
+pub fn add_task(to_do_list: &mut ToDoList, args: &AddArgs, config: &Config) -> Result<AddOutcome> {
+    let importance = match args.importance.as_deref() {
+        Some(importance_str) => importance_str.parse::<Importance>()?,
+        None => config.default_importance(),
     };
 
+    for tag in &args.tags {
+        task::validate_tag(tag)?;
+    }
+
+    if let Some(parent) = args.parent {
+        to_do_list.get(parent).ok_or(Error::NotFound(parent))?;
+    }
+
     // We need to clone the message because args is a reference.
-    let new_id = to_do_list.add_task(args.message.clone(), importance);
-    Ok(format!("Successfully added task with ID: {}", new_id))
+    let id = to_do_list.add_task(args.message.clone(), importance);
+    to_do_list.tag_task(id, &args.tags, &[])?;
+    to_do_list.set_parent(id, args.parent)?;
+    if let Some(description) = &args.description {
+        to_do_list.describe_task(id, description)?;
+    }
+    Ok(AddOutcome { id })
 }
 
-/// Updates an existing task's status or importance. Takes a reference to UpdateArgs.
-pub fn update_task(to_do_list: &mut ToDoList, args: &UpdateArgs) -> Result<String, String> {
-    let status = match args.status.as_deref().map(|s| s.parse::<Status>()).transpose() {
-        Ok(s) => s,
-        Err(_) => return Err("Invalid status provided. Use 'pending' or 'finished'.".to_string()),
-    };
+/// Parses a `--where` query, expanding saved `@name`s from `config`.
+fn filter_query(filter: Option<&str>, config: &Config) -> Result<Option<Query>> {
+    filter.map(|filter| Query::parse(filter, |name| config.saved_query(name))).transpose()
+}
 
-    let importance = match args.importance.as_deref().map(|s| s.parse::<Importance>()).transpose() {
-        Ok(i) => i,
-        Err(_) => return Err("Invalid importance provided. Use 'normal', 'important', or 'urgent'.".to_string()),
-    };
+/// Updates the status, importance, tags or parent of every selected task. Any error
+/// aborts the command before the list is saved, so either every task changes or none do.
+pub fn update_task(to_do_list: &mut ToDoList, args: &UpdateArgs, config: &Config) -> Result<UpdateOutcome> {
+    let status = args.status.as_deref().map(|s| s.parse::<Status>()).transpose()?;
+    let importance = args.importance.as_deref().map(|s| s.parse::<Importance>()).transpose()?;
+    for tag in &args.tags {
+        task::validate_tag(tag)?;
+    }
+
+    let filter = filter_query(args.filter.as_deref(), config)?;
+    let ids = select::resolve_all(to_do_list, &args.tasks, filter.as_ref())?;
 
-    if to_do_list.update_task(args.id, status, importance) {
-        Ok(format!("Successfully updated task with ID: {}", args.id))
-    } else {
-        Err(format!("Task with ID {} not found.", args.id))
+    for &id in &ids {
+        if args.parent.is_some() || args.no_parent {
+            to_do_list.set_parent(id, args.parent)?;
+        }
+        to_do_list.update_task(id, status.clone(), importance.clone())?;
+        to_do_list.tag_task(id, &args.tags, &args.untags)?;
+        if let Some(description) = &args.description {
+            to_do_list.describe_task(id, description)?;
+        }
     }
+    Ok(UpdateOutcome { tasks: task_refs(to_do_list, &ids) })
 }
 
-/// Removes a task from the list. Takes a reference to RmArgs.
-pub fn remove_task(to_do_list: &mut ToDoList, args: &RmArgs) -> Result<String, String> {
-    if to_do_list.remove_task(args.id) {
-        Ok(format!("Successfully removed task with ID: {}", args.id))
-    } else {
-        Err(format!("Task with ID {} not found.", args.id))
+/// Removes every selected task. Removing more than `rm.confirm_above` at once needs `--yes`.
+pub fn remove_task(to_do_list: &mut ToDoList, args: &RmArgs, config: &Config) -> Result<RemoveOutcome> {
+    let filter = filter_query(args.filter.as_deref(), config)?;
+    let ids = select::resolve_all(to_do_list, &args.tasks, filter.as_ref())?;
+    if ids.len() > config.rm_confirm_above() && !args.yes {
+        return Err(Error::NeedsConfirmation(ids.len()));
+    }
+
+    let tasks = task_refs(to_do_list, &ids);
+    for id in ids {
+        to_do_list.remove_task(id)?;
     }
+    Ok(RemoveOutcome { tasks })
 }
 
-/// Generates a string displaying all tasks.
-pub fn show_tasks(to_do_list: &ToDoList) -> Result<String, String> {
-    if to_do_list.is_empty() {
-        return Ok("No tasks to show.".to_string());
+/// Parses `show`'s query words, expanding `@name` from the saved queries in `config`.
+fn show_query(args: &ShowArgs, config: &Config) -> Result<Query> {
+    Query::parse(&args.query.join(" "), |name| config.saved_query(name))
+}
+
+/// Sorts, pages and groups the matching tasks as `show`'s options ask.
+/// `lookup` finds any task in the shown lists, for naming parent groups.
+fn arrange<'a, F>(mut tasks: Vec<ListedTask>, args: &ShowArgs, lookup: F) -> ShowOutcome
+where
+    F: Fn(Option<Origin>, TaskId) -> Option<&'a Task>,
+{
+    sort::sort_tasks(&mut tasks, &args.sort);
+    let tasks: Vec<ListedTask> = tasks.into_iter().skip(args.offset).take(args.limit.unwrap_or(usize::MAX)).collect();
+    let groups = args
+        .group_by
+        .map(|group_by| sort::group_tasks(&tasks, group_by, |origin, id| lookup(origin, id).map(|t| t.name.clone())));
+    ShowOutcome { tasks, groups }
+}
+
+/// Lists the tasks matching the query, or all of them without one.
+pub fn show_tasks(to_do_list: &ToDoList, args: &ShowArgs, config: &Config) -> Result<ShowOutcome> {
+    let query = show_query(args, config)?;
+    let now = task::now();
+    let tasks = to_do_list
+        .tasks()
+        .iter()
+        .filter(|task| query.matches(task, now))
+        .map(|task| ListedTask { origin: None, task: task.clone() })
+        .collect();
+    Ok(arrange(tasks, args, |_, id| to_do_list.get(id)))
+}
+
+/// Searches task names, descriptions and tags for every word in `args.terms`.
+pub fn search_tasks(to_do_list: &ToDoList, args: &SearchArgs) -> Result<SearchOutcome> {
+    // `rudden search "login page"` searches for both words, like `rudden search login page`.
+    let terms: Vec<String> = args.terms.iter().flat_map(|t| t.split_whitespace()).map(str::to_string).collect();
+    Ok(SearchOutcome { hits: search::search(to_do_list.tasks(), &terms, args.fuzzy) })
+}
+
+/// Starts a task, checking out its branch when run inside a git repository.
+pub fn start_task(to_do_list: &mut ToDoList, repo_path: &Path, args: &StartArgs, config: &Config) -> Result<StartOutcome> {
+    let id = select::resolve(to_do_list, &args.task)?;
+    let task = to_do_list.get(id).ok_or(Error::NotFound(id))?;
+    if task.status == Status::Finished {
+        return Err(Error::AlreadyFinished(id));
+    }
+
+    let mut branch = None;
+    if !args.no_branch && git::git_dir(repo_path).is_some() {
+        let branch_name = branch::branch_name(config.branch_template(), task);
+        git::checkout_branch(repo_path, &branch_name)?;
+        branch = Some(branch_name);
     }
 
-    let mut output = format!("There are {} Tasks:\n", to_do_list.tasks().len());
-    for task in to_do_list.tasks() {
-        let line = format!(
-            "- [id: {}] {} (Status: {}, Importance: {})\n",
-            task.id, task.name, task.status, task.importance
-        );
-        output.push_str(&line);
+    to_do_list.update_task(id, Some(Status::Started), None)?;
+    Ok(StartOutcome { id, branch })
+}
+
+/// Lists repository and personal tasks together, each marked with its origin.
+pub fn show_all_tasks(repo_list: &ToDoList, global_list: &ToDoList, args: &ShowArgs, config: &Config) -> Result<ShowOutcome> {
+    let query = show_query(args, config)?;
+    let now = task::now();
+    let origins = [(Origin::Repo, repo_list), (Origin::Global, global_list)];
+    let tasks = origins
+        .into_iter()
+        .flat_map(|(origin, list)| list.tasks().iter().map(move |task| (origin, task)))
+        .filter(|(_, task)| query.matches(task, now))
+        .map(|(origin, task)| ListedTask { origin: Some(origin), task: task.clone() })
+        .collect();
+    Ok(arrange(tasks, args, |origin, id| match origin {
+        Some(Origin::Global) => global_list.get(id),
+        _ => repo_list.get(id),
+    }))
+}
+
+/// Summarizes each named task list, then collects the most important open tasks across all of them.
+pub fn dashboard(lists: &[(String, ToDoList)], args: &DashArgs) -> Result<DashOutcome> {
+    let mut repositories = Vec::new();
+    let mut open_tasks: Vec<(&str, &Task)> = Vec::new();
+    for (name, list) in lists {
+        let count_status = |s: Status| list.tasks().iter().filter(|t| t.status == s).count();
+        let count_importance = |i: Importance| list.tasks().iter().filter(|t| t.importance == i).count();
+        repositories.push(RepoSummary {
+            name: name.clone(),
+            pending: count_status(Status::Pending),
+            started: count_status(Status::Started),
+            finished: count_status(Status::Finished),
+            normal: count_importance(Importance::Normal),
+            important: count_importance(Importance::Important),
+            urgent: count_importance(Importance::Urgent),
+        });
+        open_tasks.extend(list.tasks().iter().filter(|t| t.status != Status::Finished).map(|t| (name.as_str(), t)));
     }
-    // Remove the final newline for a cleaner output
-    Ok(output.trim_end().to_string())
+
+    // Stable sort keeps repository order among tasks of the same importance.
+    open_tasks.sort_by(|a, b| b.1.importance.cmp(&a.1.importance));
+    let top = open_tasks
+        .into_iter()
+        .take(args.top)
+        .map(|(name, task)| DashTask { repository: name.to_string(), task: task.clone() })
+        .collect();
+    Ok(DashOutcome { repositories, top })
 }
 
 /// Checks git logs and updates task statuses.
-pub fn check_tasks(to_do_list: &mut ToDoList, repo_path: &Path) -> io::Result<String> {
-    let logs_path = repo_path.join(".git").join("logs").join("HEAD");
+///
+/// Besides matching task names, commits on a task branch start that task, merging the
+/// branch into the main branch finishes it, and so does a `Closes #<id>` line in a commit
+/// message. Only reflog entries after `last_checked` are
+/// looked at, and it is moved on to the newest one.
+pub fn check_tasks(to_do_list: &mut ToDoList, archive: &mut ToDoList, repo_path: &Path, config: &Config, last_checked: &mut Option<String>) -> Result<CheckOutcome> {
+    let not_found = CheckOutcome { repository_found: false, started: Vec::new(), finished: Vec::new(), archived: Vec::new() };
+    let logs_path = match config.logs_path() {
+        Some(path) => repo_path.join(path),
+        None => match git::git_dir(repo_path) {
+            Some(git_dir) => git_dir.join("logs").join("HEAD"),
+            None => return Ok(not_found),
+        },
+    };
     let logs_content = match fs::read_to_string(logs_path) {
         Ok(content) => content,
-        Err(e) if e.kind() == io::ErrorKind::NotFound => {
-            return Ok("No git repository found, can't check logs.".to_string());
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(not_found),
+        Err(e) => return Err(e.into()),
+    };
+
+    // Only entries since the last run count, so a task reopened on purpose stays open.
+    let from = git::unprocessed(&logs_content, last_checked.as_deref());
+    let mut finished = to_do_list.update_statuses_from_logs(&logs_content[from..]);
+    let mut started = Vec::new();
+    for event in git::branch_events(&logs_content, from) {
+        match event {
+            BranchEvent::Commit { branch } => {
+                if let Some(id) = branch::task_id(config.branch_template(), &branch).filter(|id| to_do_list.start_task(*id)) {
+                    started.push(id);
+                }
+            }
+            BranchEvent::Merge { from, into } if config.is_main_branch(&into) => {
+                if let Some(id) = branch::task_id(config.branch_template(), &from).filter(|id| to_do_list.finish_task(*id)) {
+                    finished.push(id);
+                }
+            }
+            BranchEvent::Merge { .. } => {}
+        }
+    }
+    for message in git::commit_messages(repo_path, &logs_content, from) {
+        for id in hooks::closed_ids(&message) {
+            if to_do_list.finish_task(id) {
+                finished.push(id);
+            }
+        }
+    }
+
+    if let Some(entry) = git::last_entry(&logs_content) {
+        *last_checked = Some(entry.to_string());
+    }
+    let started = task_refs(to_do_list, &started);
+    let finished = task_refs(to_do_list, &finished);
+    let archived = match config.archive_after() {
+        Some(age) => archive_finished(to_do_list, archive, Some(age)),
+        None => Vec::new(),
+    };
+    Ok(CheckOutcome { repository_found: true, started, finished, archived })
+}
+
+/// Moves finished tasks to `archive`; with `older_than`, only those finished at least that
+/// many seconds ago.
+fn archive_finished(to_do_list: &mut ToDoList, archive: &mut ToDoList, older_than: Option<u64>) -> Vec<TaskRef> {
+    let now = task::now();
+    let ids: Vec<TaskId> = to_do_list
+        .tasks()
+        .iter()
+        // Tasks finished before rudden recorded any times count as old enough.
+        .filter(|t| t.status == Status::Finished && older_than.is_none_or(|age| t.finished_ago(now).is_none_or(|ago| ago >= age)))
+        .map(|t| t.id)
+        .collect();
+    let tasks = to_do_list.take(&ids);
+    let refs = tasks.iter().map(TaskRef::from).collect();
+    for task in tasks {
+        archive.insert(task);
+    }
+    refs
+}
+
+/// Moves finished tasks out of the task list into the archive.
+pub fn archive_tasks(to_do_list: &mut ToDoList, archive: &mut ToDoList, args: &ArchiveArgs) -> Result<ArchiveOutcome> {
+    Ok(ArchiveOutcome { tasks: archive_finished(to_do_list, archive, args.older_than) })
+}
+
+/// Moves the selected archived tasks back into the task list.
+pub fn unarchive_tasks(to_do_list: &mut ToDoList, archive: &mut ToDoList, args: &UnarchiveArgs) -> Result<UnarchiveOutcome> {
+    let ids = select::resolve_all(archive, &args.tasks, None)?;
+    let mut tasks = Vec::new();
+    for task in archive.take(&ids) {
+        let name = task.name.clone();
+        let id = to_do_list.insert(task);
+        tasks.push(TaskRef { id, name });
+    }
+    Ok(UnarchiveOutcome { tasks })
+}
+
+/// Adds the tasks in an import file. A task named like one already in the list, ignoring
+/// case, is skipped, so importing the same file twice adds nothing the second time.
+pub fn import_tasks(to_do_list: &mut ToDoList, args: &ImportArgs) -> Result<ImportOutcome> {
+    let content = if args.file.as_os_str() == "-" { io::read_to_string(io::stdin())? } else { fs::read_to_string(&args.file)? };
+    let imported = import::parse(&content, args.from)?;
+
+    let parents: Vec<Option<usize>> = imported.iter().map(|t| t.parent).collect();
+    let mut created = Vec::new();
+    let mut skipped = Vec::new();
+    // The ID each imported task ended up as, created or existing, so subtasks can find their
+    // parent, and whether it was created.
+    let mut ids = Vec::with_capacity(imported.len());
+    for ImportedTask { mut task, .. } in imported {
+        let existing = to_do_list.tasks().iter().find(|t| t.name.trim().eq_ignore_ascii_case(task.name.trim()));
+        if let Some(existing) = existing {
+            ids.push((existing.id, false));
+            skipped.push(TaskRef::from(existing));
+            continue;
+        }
+        for tag in &task.tags {
+            task::validate_tag(tag)?;
+        }
+        task.id = 0;
+        task.parent = None;
+        // A task finished before rudden saw it can't have been added now.
+        task.created = task.created.or(task.finished).or_else(|| Some(task::now()));
+        let name = task.name.clone();
+        let id = to_do_list.insert(task);
+        ids.push((id, true));
+        created.push(TaskRef { id, name });
+    }
+    // Existing tasks keep their parents.
+    for (parent, (id, new)) in parents.iter().zip(&ids) {
+        if let (Some(parent), true) = (parent, new) {
+            to_do_list.set_parent(*id, Some(ids[*parent].0))?;
+        }
+    }
+    Ok(ImportOutcome { created, skipped })
+}
+
+/// Writes the tasks matching the query, or all of them, as a document to share elsewhere.
+pub fn export_tasks(to_do_list: &ToDoList, args: &ExportArgs, config: &Config) -> Result<ExportOutcome> {
+    let query = Query::parse(&args.query.join(" "), |name| config.saved_query(name))?;
+    let now = task::now();
+    let tasks: Vec<Task> = to_do_list.tasks().iter().filter(|task| query.matches(task, now)).cloned().collect();
+    Ok(ExportOutcome { ids: tasks.iter().map(|task| task.id).collect(), document: export::export(&tasks, args.to, now) })
+}
+
+/// Keeps a task for every TODO, FIXME and HACK comment under the scanned paths. A comment
+/// keeps its task when it moves, or when its text changes but its line doesn't; a task whose
+/// comment is gone is finished, as `check` finishes tasks from commits.
+pub fn scan_tasks(to_do_list: &mut ToDoList, repo_path: &Path, args: &ScanArgs, config: &Config) -> Result<ScanOutcome> {
+    let root = repo_path.canonicalize()?;
+    let paths = args.paths.iter().map(|path| path.canonicalize()).collect::<io::Result<Vec<_>>>()?;
+    let comments = scan::find_comments(&root, &paths)?;
+    let scanned: Vec<String> = paths.iter().map(|path| scan::relative(&root, path)).collect();
+    let is_scanned = |location: &task::Location| {
+        scanned.is_empty()
+            || scanned.iter().any(|path| location.path == *path || location.path.starts_with(&format!("{}/", path)))
+    };
+
+    // Match comments to tasks with the same text in the same file first, so moved comments
+    // keep their tasks, then to tasks at the same line, for edited ones.
+    let linked = |task: &Task, comment: &scan::Comment, exact: bool| {
+        task.location.as_ref().is_some_and(|location| {
+            location.path == comment.location.path && if exact { task.name == comment.text } else { location.line == comment.location.line }
+        })
+    };
+    let mut matched: Vec<Option<TaskId>> = vec![None; comments.len()];
+    for exact in [true, false] {
+        for (index, comment) in comments.iter().enumerate() {
+            if matched[index].is_some() {
+                continue;
+            }
+            matched[index] = to_do_list
+                .tasks()
+                .iter()
+                .find(|task| linked(task, comment, exact) && !matched.contains(&Some(task.id)))
+                .map(|task| task.id);
+        }
+    }
+
+    let mut outcome = ScanOutcome { comments: comments.len(), created: Vec::new(), updated: Vec::new(), finished: Vec::new() };
+    for (comment, id) in comments.into_iter().zip(matched.iter_mut()) {
+        match id.and_then(|id| to_do_list.get(id)) {
+            Some(task) => {
+                let unchanged = task.name == comment.text && task.location.as_ref() == Some(&comment.location) && task.status != Status::Finished;
+                if unchanged {
+                    continue;
+                }
+                let id = task.id;
+                if task.status == Status::Finished {
+                    to_do_list.update_task(id, Some(Status::Pending), None)?;
+                }
+                to_do_list.link_task(id, &comment.text, comment.location)?;
+                outcome.updated.push(TaskRef { id, name: comment.text });
+            }
+            None => {
+                let new = to_do_list.add_task(comment.text.clone(), config.default_importance());
+                to_do_list.tag_task(new, &[comment.marker.to_lowercase()], &[])?;
+                to_do_list.link_task(new, &comment.text, comment.location)?;
+                *id = Some(new);
+                outcome.created.push(TaskRef { id: new, name: comment.text });
+            }
+        }
+    }
+
+    let gone: Vec<TaskId> = to_do_list
+        .tasks()
+        .iter()
+        .filter(|task| task.location.as_ref().is_some_and(&is_scanned) && !matched.contains(&Some(task.id)))
+        .map(|task| task.id)
+        .collect();
+    for id in gone {
+        if to_do_list.finish_task(id) {
+            outcome.finished.extend(to_do_list.get(id).map(TaskRef::from));
+        }
+    }
+    Ok(outcome)
+}
+
+fn task_refs(to_do_list: &ToDoList, ids: &[TaskId]) -> Vec<TaskRef> {
+    ids.iter().filter_map(|id| to_do_list.get(*id)).map(TaskRef::from).collect()
+}
+
+/// Installs, removes or reports the git hooks that keep tasks in sync with commits.
+pub fn manage_hooks(to_do_list: &ToDoList, repo_path: &Path, args: &HooksArgs, config: &Config) -> Result<HooksOutcome> {
+    if let HooksAction::CheckMsg { file } = &args.action {
+        let message = fs::read_to_string(file)?;
+        return Ok(HooksOutcome::MessageChecked { unknown: check_commit_message(to_do_list, &message) });
+    }
+    if let HooksAction::PrepareMsg { file, source } = &args.action {
+        // Only fill in plain `git commit`; -m, -F, merges and amends already carry a message.
+        if source.as_deref().unwrap_or("").is_empty() {
+            let message = fs::read_to_string(file)?;
+            let branch_task = git::current_branch(repo_path).and_then(|branch| branch::task_id(config.branch_template(), &branch));
+            fs::write(file, prepare_commit_message(to_do_list, &message, branch_task, git::comment_char(repo_path)))?;
         }
-        Err(e) => return Err(e),
+        return Ok(HooksOutcome::MessagePrepared);
+    }
+
+    if git::git_dir(repo_path).is_none() {
+        return Ok(HooksOutcome::NoRepository);
+    }
+    let hooks_dir = git::hooks_dir(repo_path);
+
+    let mut statuses = Vec::new();
+    for hook in hooks::Hook::ALL {
+        let state = match args.action {
+            HooksAction::Install => hooks::install(&hooks_dir, hook)?,
+            HooksAction::Uninstall => hooks::uninstall(&hooks_dir, hook)?,
+            _ => hooks::state(&hooks_dir, hook)?,
+        };
+        statuses.push(HookStatus { hook, state });
+    }
+    Ok(HooksOutcome::States { hooks: statuses })
+}
+
+/// Returns every `#<id>` in a commit message that doesn't match a task.
+pub fn check_commit_message(to_do_list: &ToDoList, message: &str) -> Vec<TaskId> {
+    hooks::referenced_ids(message)
+        .into_iter()
+        .filter(|id| to_do_list.get(*id).is_none())
+        .collect()
+}
+
+/// Builds a commit message that closes the given tasks.
+pub fn commit_message(to_do_list: &ToDoList, args: &CommitMsgArgs) -> Result<CommitMsgOutcome> {
+    let ids = select::resolve_all(to_do_list, &args.tasks, None)?;
+    let tasks: Vec<&Task> = ids.iter().filter_map(|id| to_do_list.get(*id)).collect();
+    Ok(CommitMsgOutcome { ids, message: build_commit_message(&tasks) })
+}
+
+/// The subject carries the task names, since `check` matches names against the git log,
+/// and the body carries one `Closes #id` trailer per task.
+fn build_commit_message(tasks: &[&Task]) -> String {
+    let subject: Vec<&str> = tasks.iter().map(|t| t.name.as_str()).collect();
+    let trailers: Vec<String> = tasks.iter().map(|t| format!("Closes #{}", t.id)).collect();
+    format!("{}\n\n{}", subject.join("; "), trailers.join("\n"))
+}
+
+/// Pre-fills the message git is preparing with the task being worked on, as `commit-msg`
+/// writes it: the open task of the current branch, or else every started task. The other
+/// open tasks are listed in comment lines, starting with `comment_char`, which git strips.
+pub fn prepare_commit_message(to_do_list: &ToDoList, message: &str, branch_task: Option<TaskId>, comment_char: char) -> String {
+    let open: Vec<&Task> = to_do_list.tasks().iter().filter(|t| t.status != Status::Finished).collect();
+    let selected: Vec<&Task> = match branch_task.and_then(|id| open.iter().find(|t| t.id == id)) {
+        Some(task) => vec![task],
+        None => open.iter().copied().filter(|t| t.status == Status::Started).collect(),
     };
+    let others: Vec<&Task> = open.iter().copied().filter(|t| !selected.iter().any(|s| s.id == t.id)).collect();
 
-    if to_do_list.update_statuses_from_logs(&logs_content) {
-        Ok("Tasks updated successfully based on git logs.".to_string())
-    } else {
-        Ok("No tasks to update from git logs.".to_string())
+    let mut prepared = String::new();
+    if !selected.is_empty() {
+        prepared.push_str(&format!("{}\n", build_commit_message(&selected)));
     }
+    if !others.is_empty() {
+        prepared.push_str(&format!("\n{} Other open rudden tasks; add a `Closes #<id>` line to close one:\n", comment_char));
+        for task in others {
+            prepared.push_str(&format!("{}   #{} {}\n", comment_char, task.id, task.name));
+        }
+    }
+    format!("{}{}", prepared, message)
+}
+
+/// Reads or writes configuration. `set` targets the repository's config file unless `--user` is given.
+pub fn manage_config(config: &Config, args: &ConfigArgs, repo_config: &Path, user_config: Option<&Path>) -> Result<ConfigOutcome> {
+    match &args.action {
+        ConfigAction::Get { key } => {
+            if !Config::is_known_key(key) {
+                return Err(Error::Config(ConfigError {
+                    key: key.clone(),
+                    source: Source::Cli,
+                    message: "unknown configuration key".to_string(),
+                }));
+            }
+            let value = config.get(key).map(|(value, _)| value.to_string());
+            Ok(ConfigOutcome::Value { key: key.clone(), value })
+        }
+        ConfigAction::Set { key, value, user } => {
+            let path = if *user {
+                user_config.ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::NotFound, "can't locate the user config: neither XDG_CONFIG_HOME nor HOME is set")
+                })?
+            } else if repo_config.parent().is_some_and(Path::is_dir) {
+                repo_config
+            } else {
+                return Err(Error::StoreNotFound);
+            };
+            let source = if *user { Source::User(path.to_path_buf()) } else { Source::Repo(path.to_path_buf()) };
+            config::write_value(path, key, value, source)?;
+            Ok(ConfigOutcome::Set { key: key.clone(), value: value.clone(), path: path.to_path_buf() })
+        }
+        ConfigAction::List => {
+            let saved = config.saved_queries().into_iter().map(|name| format!("query.{}", name));
+            let entries = Config::keys()
+                .map(str::to_string)
+                .chain(saved)
+                .map(|key| {
+                    let found = config.get(&key);
+                    ConfigEntry {
+                        key,
+                        value: found.map(|(value, _)| value.to_string()),
+                        source: found.map(|(_, source)| source.to_string()),
+                    }
+                })
+                .collect();
+            Ok(ConfigOutcome::List { entries })
+        }
+    }
+}
+
+/// Writes the Markdown reference and the man pages, both generated from the clap definitions.
+pub fn generate_docs(args: &GenDocsArgs) -> Result<DocsOutcome> {
+    let man_dir = args.out_dir.join(docs::MAN_DIR);
+    fs::create_dir_all(&man_dir)?;
+    let reference = args.out_dir.join(docs::REFERENCE_FILE);
+    fs::write(&reference, docs::markdown())?;
+    Ok(DocsOutcome { reference, man_pages: docs::man_pages(&man_dir)? })
 }
 
 /// Runs a benchmark comparison between the slow (read/write) and fast (append-only) add operations.
-pub fn run_benchmark(args: &BenchArgs) -> Result<String, String> {
+pub fn run_benchmark(args: &BenchArgs) -> Result<BenchOutcome> {
     let num_tasks = args.tasks;
     let slow_path = Path::new(".rudden_slow_bench.csv");
     let fast_path = Path::new(".rudden_fast_bench.csv");
     let meta_path = Path::new(".rudden_meta.json");
 
-    println!("Starting benchmark with {} tasks...", num_tasks);
-
     // --- SLOW METHOD (Read-Modify-Write) ---
-    persistence::clear_benchmark_data(slow_path, fast_path, meta_path).map_err(|e| e.to_string())?;
+    persistence::clear_benchmark_data(slow_path, fast_path, meta_path)?;
     let mut slow_list = ToDoList::default();
     let slow_start = Instant::now();
     for i in 0..num_tasks {
         slow_list.add_task(format!("Task {}", i), Importance::Normal);
         // This save call is the O(N) bottleneck
-        slow_list.save(slow_path.to_str().unwrap()).map_err(|e| e.to_string())?;
+        slow_list.save(slow_path.to_str().unwrap())?;
     }
     let slow_duration = slow_start.elapsed();
 
-    println!("Slow method finished.");
-
     // --- FAST METHOD (Append-Only) ---
     let fast_start = Instant::now();
     for i in 0..num_tasks {
-        persistence::add_task_fast(fast_path, meta_path, format!("Task {}", i), Importance::Normal)
-            .map_err(|e| e.to_string())?;
+        persistence::add_task_fast(fast_path, meta_path, format!("Task {}", i), Importance::Normal)?;
     }
     let fast_duration = fast_start.elapsed();
-    
-    println!("Fast method finished.");
 
     // --- CLEANUP ---
-    persistence::clear_benchmark_data(slow_path, fast_path, meta_path).map_err(|e| e.to_string())?;
-
-    // --- REPORT ---
-    let report = format!(
-        "\n--- Benchmark Results ---\n\
-        Tasks Added: {}\n\n\
-        Slow Method (O(N) Read/Write): {:?}\n\
-        Fast Method (O(1) Append-Only): {:?}\n\n\
-        Conclusion: The append-only method was {:.2}x faster.",
-        num_tasks,
-        slow_duration,
-        fast_duration,
-        slow_duration.as_secs_f64() / fast_duration.as_secs_f64()
-    );
-    
-    Ok(report)
+    persistence::clear_benchmark_data(slow_path, fast_path, meta_path)?;
+
+    Ok(BenchOutcome { tasks: num_tasks, slow: slow_duration, fast: fast_duration })
 }
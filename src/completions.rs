@@ -0,0 +1,145 @@
+use clap::{CommandFactory, ValueEnum};
+use clap_complete::Shell;
+
+use crate::cli::Cli;
+use crate::shell::TAKES_TASKS;
+use crate::task::ToDoList;
+
+const BIN: &str = "rudden";
+
+/// The shells `rudden completions` writes scripts for.
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CompletionShell {
+    Bash,
+    Zsh,
+    Fish,
+    Elvish,
+}
+
+/// The completion script for `shell`. Clap's generated script completes subcommands and
+/// options; the additions ask `rudden __complete` for task IDs wherever a command takes tasks.
+pub fn script(shell: CompletionShell) -> String {
+    let generator = match shell {
+        CompletionShell::Bash => Shell::Bash,
+        CompletionShell::Zsh => Shell::Zsh,
+        CompletionShell::Fish => Shell::Fish,
+        CompletionShell::Elvish => Shell::Elvish,
+    };
+    let mut buffer = Vec::new();
+    clap_complete::generate(generator, &mut Cli::command(), BIN, &mut buffer);
+    let generated = String::from_utf8_lossy(&buffer);
+    let commands = TAKES_TASKS.join(" ");
+
+    match shell {
+        CompletionShell::Bash => {
+            // Completion goes through a wrapper that handles task arguments itself.
+            let generated = generated.replace("complete -F _rudden ", "complete -F _rudden_tasks ");
+            format!(
+                r#"{generated}
+_rudden_tasks() {{
+    local cur="${{COMP_WORDS[COMP_CWORD]}}" prev="${{COMP_WORDS[COMP_CWORD-1]}}"
+    case " {commands} " in
+        *" ${{COMP_WORDS[1]}} "*)
+            if [[ ${{COMP_CWORD}} -ge 2 && ${{cur}} != -* && ${{prev}} != -* ]]; then
+                local IFS=$'\n'
+                COMPREPLY=( $(compgen -W "$({BIN} __complete 2>/dev/null | cut -f1)" -- "${{cur}}") )
+                return 0
+            fi
+            ;;
+    esac
+    _rudden "$@"
+}}
+"#
+            )
+        }
+        CompletionShell::Zsh => {
+            // Each task argument's `_default` action becomes `_rudden_tasks`.
+            let mut subcommand = "";
+            let mut lines = Vec::new();
+            for line in generated.lines() {
+                if let Some(name) = line.strip_prefix('(').and_then(|rest| rest.strip_suffix(')')) {
+                    subcommand = name;
+                }
+                let takes_tasks = TAKES_TASKS.contains(&subcommand) && (line.starts_with("'*::tasks -- ") || line.starts_with("':task -- "));
+                if line == r#"if [ "$funcstack[1]" = "_rudden" ]; then"# {
+                    lines.push(format!(
+                        r#"(( $+functions[_rudden_tasks] )) ||
+_rudden_tasks() {{
+    local -a tasks
+    local line
+    for line in ${{(f)"$({BIN} __complete 2>/dev/null)"}}; do
+        tasks+=("${{line/$'\t'/:}}")
+    done
+    _describe -t tasks 'task' tasks
+}}
+"#
+                    ));
+                }
+                lines.push(if takes_tasks { line.replace(":_default'", ":_rudden_tasks'") } else { line.to_string() });
+            }
+            lines.join("\n") + "\n"
+        }
+        CompletionShell::Fish => format!(
+            "{generated}complete -c {BIN} -n \"__fish_seen_subcommand_from {commands}\" -f -a \"({BIN} __complete 2>/dev/null)\"\n"
+        ),
+        CompletionShell::Elvish => format!(
+            r#"{generated}
+var rudden-static = $edit:completion:arg-completer[{BIN}]
+set edit:completion:arg-completer[{BIN}] = {{|@words|
+    if (and (> (count $words) 2) (has-value [{commands}] $words[1]) (not (str:has-prefix $words[-1] -))) {{
+        {BIN} __complete 2>/dev/null | from-lines | each {{|line|
+            var id name = (str:split &max=2 "\t" $line)
+            edit:complex-candidate $id &display=$id'  '$name
+        }}
+    }} else {{
+        $rudden-static $@words
+    }}
+}}
+"#
+        ),
+    }
+}
+
+/// The tasks as completion candidates: one `ID<TAB>name` line per task, which fish and zsh
+/// show as the ID with the name beside it.
+pub fn task_candidates(to_do_list: &ToDoList) -> String {
+    to_do_list.tasks().iter().map(|task| format!("{}\t{}", task.id, task.name.replace('\t', " "))).collect::<Vec<_>>().join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::Importance;
+
+    // Test 1: Every script asks `rudden __complete` for tasks, and zsh uses it for task arguments
+    #[test]
+    fn test_scripts_complete_tasks() {
+        // Arrange
+        let shells = CompletionShell::value_variants();
+
+        // Act
+        let scripts: Vec<String> = shells.iter().map(|shell| script(*shell)).collect();
+
+        // Assert
+        assert!(scripts.iter().all(|script| script.contains("rudden __complete")));
+        assert!(scripts[0].contains("complete -F _rudden_tasks"));
+        assert!(!scripts[0].contains("complete -F _rudden "));
+        let zsh_task_arguments = scripts[1].lines().filter(|line| line.ends_with(":_rudden_tasks' \\")).count();
+        assert_eq!(zsh_task_arguments, TAKES_TASKS.len());
+    }
+
+    // Test 2: Candidates are the task IDs with their names
+    #[test]
+    fn test_task_candidates() {
+        // Arrange
+        let mut to_do_list = ToDoList::default();
+        to_do_list.add_task("Fix login".to_string(), Importance::Normal);
+        to_do_list.add_task("Write\tdocs".to_string(), Importance::Normal);
+
+        // Act
+        let candidates = task_candidates(&to_do_list);
+
+        // Assert
+        assert_eq!(candidates, "1\tFix login\n2\tWrite docs");
+    }
+}
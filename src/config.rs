@@ -0,0 +1,627 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::ffi::OsString;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::branch;
+use crate::query;
+use crate::table::{ColorChoice, Column};
+use crate::task::Importance;
+
+/// Name of the configuration file, both in the user config dir and in a repo's store.
+pub const CONFIG_FILE: &str = "config.toml";
+
+/// Where a configuration value came from. Later variants take precedence.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum Source {
+    Default,
+    User(PathBuf),
+    Repo(PathBuf),
+    Env(String),
+    Cli,
+}
+
+impl Source {
+    fn rank(&self) -> u8 {
+        match self {
+            Self::Default => 0,
+            Self::User(_) => 1,
+            Self::Repo(_) => 2,
+            Self::Env(_) => 3,
+            Self::Cli => 4,
+        }
+    }
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Default => write!(f, "default"),
+            Self::User(path) => write!(f, "user config {}", path.display()),
+            Self::Repo(path) => write!(f, "repo config {}", path.display()),
+            Self::Env(var) => write!(f, "environment variable {}", var),
+            Self::Cli => write!(f, "command line"),
+        }
+    }
+}
+
+/// A configuration problem, pointing at the key and the layer that caused it.
+#[derive(Debug)]
+pub struct ConfigError {
+    pub key: String,
+    pub source: Source,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.key.is_empty() {
+            write!(f, "{}: {}", self.source, self.message)
+        } else {
+            write!(f, "`{}` ({}): {}", self.key, self.source, self.message)
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+struct KeyDef {
+    name: &'static str,
+    default: Option<&'static str>,
+    validate: fn(&str) -> Result<(), String>,
+}
+
+const KEYS: &[KeyDef] = &[
+    KeyDef { name: "store.dir", default: None, validate: validate_non_empty },
+    KeyDef { name: "add.default_importance", default: Some("normal"), validate: validate_importance },
+    // Unset by default, so `check` reads the HEAD reflog wherever git keeps it.
+    KeyDef { name: "check.logs_path", default: None, validate: validate_non_empty },
+    // Unset by default, so `check` never archives.
+    KeyDef { name: "check.archive_after", default: None, validate: validate_duration },
+    KeyDef { name: "branch.template", default: Some(branch::DEFAULT_TEMPLATE), validate: validate_branch_template },
+    KeyDef { name: "branch.main", default: None, validate: validate_non_empty },
+    KeyDef { name: "rm.confirm_above", default: Some("5"), validate: validate_count },
+    KeyDef { name: "show.empty", default: Some("No tasks to show."), validate: validate_any },
+    KeyDef { name: "show.header", default: Some("There are {count} Tasks:"), validate: validate_show_header },
+    KeyDef {
+        name: "show.line",
+        default: Some("- [id: {id}] {name} (Status: {status}, Importance: {importance})"),
+        validate: validate_show_line,
+    },
+    KeyDef { name: "show.style", default: Some("list"), validate: validate_show_style },
+    KeyDef { name: "show.columns", default: Some("id,status,importance,age,tags,name"), validate: validate_show_columns },
+    KeyDef { name: "show.color", default: Some("auto"), validate: validate_color },
+    KeyDef { name: "show.width", default: Some("auto"), validate: validate_width },
+    // Saved queries, used as `rudden show @name`.
+    KeyDef { name: "query.*", default: None, validate: validate_query },
+];
+
+fn validate_any(_: &str) -> Result<(), String> {
+    Ok(())
+}
+
+fn validate_non_empty(value: &str) -> Result<(), String> {
+    if value.trim().is_empty() {
+        return Err("must not be empty".to_string());
+    }
+    Ok(())
+}
+
+fn validate_importance(value: &str) -> Result<(), String> {
+    Importance::from_str(value)
+        .map(|_| ())
+        .map_err(|_| format!("'{}' is not a valid importance. Use 'normal', 'important', or 'urgent'.", value))
+}
+
+fn validate_duration(value: &str) -> Result<(), String> {
+    query::parse_duration(value)
+        .map(|_| ())
+        .ok_or_else(|| format!("'{}' is not a valid duration. Use e.g. 30m, 12h, 7d or 2w.", value))
+}
+
+fn validate_count(value: &str) -> Result<(), String> {
+    value.parse::<usize>().map(|_| ()).map_err(|_| format!("'{}' is not a valid count. Use a whole number.", value))
+}
+
+fn validate_branch_template(value: &str) -> Result<(), String> {
+    validate_placeholders(value, &["id", "slug"])?;
+    if !value.contains("{id}") {
+        return Err("must contain {id} so branches can be traced back to their task".to_string());
+    }
+    Ok(())
+}
+
+fn validate_show_header(value: &str) -> Result<(), String> {
+    validate_placeholders(value, &["count"])
+}
+
+fn validate_show_line(value: &str) -> Result<(), String> {
+    validate_placeholders(value, &["id", "name", "status", "importance"])
+}
+
+fn validate_show_style(value: &str) -> Result<(), String> {
+    match value {
+        "list" | "table" => Ok(()),
+        _ => Err(format!("'{}' is not a valid style. Use 'list' or 'table'.", value)),
+    }
+}
+
+fn validate_show_columns(value: &str) -> Result<(), String> {
+    parse_columns(value).map(|_| ())
+}
+
+fn validate_color(value: &str) -> Result<(), String> {
+    ColorChoice::from_str(value).map(|_| ())
+}
+
+fn validate_width(value: &str) -> Result<(), String> {
+    if value == "auto" || value.parse::<usize>().is_ok() {
+        return Ok(());
+    }
+    Err(format!("'{}' is not a valid width. Use 'auto' or a number of characters.", value))
+}
+
+/// Parses a comma-separated column list such as `id,status,name`.
+pub fn parse_columns(value: &str) -> Result<Vec<Column>, String> {
+    let columns = value.split(',').map(|c| Column::from_str(c.trim())).collect::<Result<Vec<_>, _>>()?;
+    if columns.is_empty() {
+        return Err("must name at least one column".to_string());
+    }
+    Ok(columns)
+}
+
+fn validate_query(value: &str) -> Result<(), String> {
+    query::validate(value)
+}
+
+fn validate_placeholders(template: &str, allowed: &[&str]) -> Result<(), String> {
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start..].find('}') else {
+            return Err("has an unclosed '{'".to_string());
+        };
+        let name = &rest[start + 1..start + len];
+        if !allowed.contains(&name) {
+            return Err(format!("unknown placeholder {{{}}}; use {}", name, allowed.iter().map(|a| format!("{{{}}}", a)).collect::<Vec<_>>().join(", ")));
+        }
+        rest = &rest[start + len + 1..];
+    }
+    Ok(())
+}
+
+/// Finds the definition of `key`. A name ending in `*` covers every key under that section.
+fn key_def(key: &str) -> Option<&'static KeyDef> {
+    KEYS.iter().find(|k| match k.name.strip_suffix('*') {
+        Some(prefix) => key.len() > prefix.len() && key.starts_with(prefix),
+        None => k.name == key,
+    })
+}
+
+/// Name of the environment variable overriding `key`, e.g. `RUDDEN_ADD_DEFAULT_IMPORTANCE`.
+pub fn env_var(key: &str) -> String {
+    format!("RUDDEN_{}", key.to_uppercase().replace('.', "_"))
+}
+
+/// Fills `{placeholder}`s in a validated template.
+pub fn fill(template: &str, values: &[(&str, String)]) -> String {
+    values
+        .iter()
+        .fold(template.to_string(), |acc, (name, value)| acc.replace(&format!("{{{}}}", name), value))
+}
+
+/// The user's config file: `$XDG_CONFIG_HOME/rudden/config.toml`, or `~/.config/rudden/config.toml`.
+pub fn user_config_path() -> Option<PathBuf> {
+    resolve_user_config_path(env::var_os("XDG_CONFIG_HOME"), env::var_os("HOME"))
+}
+
+fn resolve_user_config_path(xdg_config_home: Option<OsString>, home: Option<OsString>) -> Option<PathBuf> {
+    let base = match xdg_config_home.map(PathBuf::from) {
+        Some(xdg) if xdg.is_absolute() => xdg,
+        _ => PathBuf::from(home?).join(".config"),
+    };
+    Some(base.join("rudden").join(CONFIG_FILE))
+}
+
+fn validate(key: &str, value: &str, source: &Source) -> Result<(), ConfigError> {
+    let error = |message: String| ConfigError { key: key.to_string(), source: source.clone(), message };
+    match key_def(key) {
+        Some(_) if key == "store.dir" && matches!(source, Source::Repo(_)) => {
+            Err(error("can't be set in the repo config, which is inside the store it would locate; set it in the user config".to_string()))
+        }
+        Some(def) => (def.validate)(value).map_err(error),
+        None => Err(error("unknown configuration key".to_string())),
+    }
+}
+
+/// Reads a config file into flat `section.key` pairs, and the entries that aren't valid.
+/// A missing file is empty.
+fn read_file(path: &Path, source: &Source) -> Result<(BTreeMap<String, String>, Vec<ConfigError>), ConfigError> {
+    let error = |key: String, message: String| ConfigError { key, source: source.clone(), message };
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok((BTreeMap::new(), Vec::new())),
+        Err(e) => return Err(error(String::new(), e.to_string())),
+    };
+    let table: toml::Table = content.parse().map_err(|e: toml::de::Error| error(String::new(), e.message().to_string()))?;
+
+    let mut values = BTreeMap::new();
+    let mut invalid = Vec::new();
+    for (section, entries) in table {
+        let Some(entries) = entries.as_table() else {
+            invalid.push(error(section, "expected a [section] table".to_string()));
+            continue;
+        };
+        for (name, value) in entries {
+            let key = format!("{}.{}", section, name);
+            let Some(value) = value.as_str() else {
+                invalid.push(error(key, "must be a string".to_string()));
+                continue;
+            };
+            match validate(&key, value, source) {
+                Ok(()) => {
+                    values.insert(key, value.to_string());
+                }
+                Err(e) => invalid.push(e),
+            }
+        }
+    }
+    Ok((values, invalid))
+}
+
+/// Sets `key` in the config file at `path`, creating it if needed. `source` is the layer the
+/// file is, which decides the keys it may hold.
+pub fn write_value(path: &Path, key: &str, value: &str, source: Source) -> Result<(), ConfigError> {
+    validate(key, value, &source)?;
+    let io_error = |e: io::Error| ConfigError { key: key.to_string(), source: Source::Cli, message: e.to_string() };
+
+    let mut table: toml::Table = match fs::read_to_string(path) {
+        Ok(content) => content.parse().map_err(|e: toml::de::Error| ConfigError {
+            key: String::new(),
+            source: Source::Cli,
+            message: format!("{}: {}", path.display(), e.message()),
+        })?,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => toml::Table::new(),
+        Err(e) => return Err(io_error(e)),
+    };
+    // Known keys always have exactly one dot.
+    let (section, name) = key.split_once('.').unwrap_or((key, ""));
+    let section = table
+        .entry(section)
+        .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+    if let Some(section) = section.as_table_mut() {
+        section.insert(name.to_string(), toml::Value::String(value.to_string()));
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(io_error)?;
+    }
+    fs::write(path, table.to_string()).map_err(io_error)
+}
+
+struct Layer {
+    source: Source,
+    values: BTreeMap<String, String>,
+}
+
+/// Layered configuration: built-in defaults, user config, repo config,
+/// environment variables and command-line flags, in increasing precedence.
+pub struct Config {
+    layers: Vec<Layer>,
+    /// Whether invalid settings are skipped and kept in `problems` instead of failing the load.
+    lenient: bool,
+    problems: Vec<ConfigError>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        let values = KEYS
+            .iter()
+            .filter_map(|k| k.default.map(|d| (k.name.to_string(), d.to_string())))
+            .collect();
+        Self { layers: vec![Layer { source: Source::Default, values }], lenient: false, problems: Vec::new() }
+    }
+}
+
+impl Config {
+    /// A configuration that loads around invalid settings, so `rudden config` can still show
+    /// and fix them.
+    pub fn lenient() -> Self {
+        Self { lenient: true, ..Self::default() }
+    }
+
+    /// The settings a lenient configuration skipped.
+    pub fn problems(&self) -> &[ConfigError] {
+        &self.problems
+    }
+
+    /// Fails on the first of `errors`, or, when lenient, keeps them all for `problems`.
+    fn tolerate(&mut self, errors: Vec<ConfigError>) -> Result<(), ConfigError> {
+        if self.lenient {
+            self.problems.extend(errors);
+            return Ok(());
+        }
+        errors.into_iter().next().map_or(Ok(()), Err)
+    }
+
+    fn load_file(&mut self, path: &Path, source: Source) -> Result<(), ConfigError> {
+        let (values, invalid) = match read_file(path, &source) {
+            Ok(read) => read,
+            Err(e) => return self.tolerate(vec![e]),
+        };
+        self.tolerate(invalid)?;
+        self.push(Layer { source, values });
+        Ok(())
+    }
+
+    fn push(&mut self, layer: Layer) {
+        self.layers.push(layer);
+        // Stable, so same-rank layers keep their load order.
+        self.layers.sort_by_key(|l| l.source.rank());
+    }
+
+    pub fn load_user(&mut self, path: &Path) -> Result<(), ConfigError> {
+        self.load_file(path, Source::User(path.to_path_buf()))
+    }
+
+    pub fn load_repo(&mut self, path: &Path) -> Result<(), ConfigError> {
+        self.load_file(path, Source::Repo(path.to_path_buf()))
+    }
+
+    /// Reads `RUDDEN_<SECTION>_<KEY>` overrides through `lookup`.
+    pub fn load_env<F: Fn(&str) -> Option<String>>(&mut self, lookup: F) -> Result<(), ConfigError> {
+        for def in KEYS.iter().filter(|k| !k.name.ends_with('*')) {
+            let var = env_var(def.name);
+            if let Some(value) = lookup(&var) {
+                let source = Source::Env(var);
+                if let Err(e) = validate(def.name, &value, &source) {
+                    self.tolerate(vec![e])?;
+                    continue;
+                }
+                let values = BTreeMap::from([(def.name.to_string(), value)]);
+                self.push(Layer { source, values });
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies a command-line flag, which beats every other layer.
+    pub fn set_cli(&mut self, key: &str, value: &str) -> Result<(), ConfigError> {
+        validate(key, value, &Source::Cli)?;
+        let values = BTreeMap::from([(key.to_string(), value.to_string())]);
+        self.push(Layer { source: Source::Cli, values });
+        Ok(())
+    }
+
+    /// The effective value of `key` and the layer it came from.
+    pub fn get(&self, key: &str) -> Option<(&str, &Source)> {
+        self.layers
+            .iter()
+            .rev()
+            .find_map(|l| l.values.get(key).map(|v| (v.as_str(), &l.source)))
+    }
+
+    pub fn is_known_key(key: &str) -> bool {
+        key_def(key).is_some()
+    }
+
+    /// Every fixed key; saved queries are listed by `saved_queries`.
+    pub fn keys() -> impl Iterator<Item = &'static str> {
+        KEYS.iter().map(|k| k.name).filter(|name| !name.ends_with('*'))
+    }
+
+    /// The names of all saved queries, from every layer.
+    pub fn saved_queries(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .layers
+            .iter()
+            .flat_map(|l| l.values.keys())
+            .filter_map(|key| key.strip_prefix("query."))
+            .map(str::to_string)
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    pub fn saved_query(&self, name: &str) -> Option<&str> {
+        self.get(&format!("query.{}", name)).map(|(v, _)| v)
+    }
+
+    fn value(&self, key: &str) -> &str {
+        // Every key read here has a built-in default.
+        self.get(key).map(|(v, _)| v).unwrap_or_default()
+    }
+
+    /// The store to use instead of discovering one. A relative path in the user config is
+    /// relative to the config file; from the environment, to the current directory.
+    pub fn store_dir(&self) -> Option<PathBuf> {
+        self.get("store.dir").map(|(v, source)| match source {
+            Source::User(path) => path.parent().unwrap_or(Path::new("")).join(v),
+            _ => PathBuf::from(v),
+        })
+    }
+
+    pub fn default_importance(&self) -> Importance {
+        // Validated on load, so parsing can't fail.
+        self.value("add.default_importance").parse().unwrap_or(Importance::Normal)
+    }
+
+    pub fn logs_path(&self) -> Option<&str> {
+        self.get("check.logs_path").map(|(v, _)| v)
+    }
+
+    /// How long after being finished `check` moves tasks to the archive, in seconds.
+    pub fn archive_after(&self) -> Option<u64> {
+        self.get("check.archive_after").and_then(|(v, _)| query::parse_duration(v))
+    }
+
+    pub fn branch_template(&self) -> &str {
+        self.value("branch.template")
+    }
+
+    /// Whether task branches merged into `branch` count as done.
+    /// Without `branch.main`, both `main` and `master` do.
+    pub fn is_main_branch(&self, branch: &str) -> bool {
+        match self.get("branch.main") {
+            Some((main, _)) => main == branch,
+            None => branch == "main" || branch == "master",
+        }
+    }
+
+    /// How many tasks `rm` removes at once before it wants `--yes`.
+    pub fn rm_confirm_above(&self) -> usize {
+        self.value("rm.confirm_above").parse().unwrap_or(5)
+    }
+
+    pub fn show_empty(&self) -> &str {
+        self.value("show.empty")
+    }
+
+    pub fn show_header(&self) -> &str {
+        self.value("show.header")
+    }
+
+    pub fn show_line(&self) -> &str {
+        self.value("show.line")
+    }
+
+    /// Whether `show` prints an aligned table instead of one templated line per task.
+    pub fn show_table(&self) -> bool {
+        self.value("show.style") == "table"
+    }
+
+    pub fn show_columns(&self) -> Vec<Column> {
+        parse_columns(self.value("show.columns")).unwrap_or_default()
+    }
+
+    pub fn color(&self) -> ColorChoice {
+        self.value("show.color").parse().unwrap_or(ColorChoice::Auto)
+    }
+
+    /// The width tables are truncated to, if any. `auto` is resolved against the terminal at startup.
+    pub fn show_width(&self) -> Option<usize> {
+        self.value("show.width").parse().ok().filter(|width| *width > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_defaults() {
+        // Arrange
+        let config = Config::default();
+
+        // Act & Assert
+        assert_eq!(config.default_importance(), Importance::Normal);
+        assert_eq!(config.logs_path(), None);
+        assert_eq!(config.get("branch.main"), None);
+        assert!(config.is_main_branch("master"));
+    }
+
+    #[test]
+    fn test_layers_override_in_order() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let user = dir.path().join("user.toml");
+        let repo = dir.path().join("repo.toml");
+        fs::write(&user, "[add]\ndefault_importance = \"important\"\n[branch]\nmain = \"develop\"\n")?;
+        fs::write(&repo, "[add]\ndefault_importance = \"urgent\"\n")?;
+
+        // Arrange
+        let mut config = Config::default();
+
+        // Act
+        config.load_repo(&repo)?;
+        config.load_user(&user)?;
+        config.load_env(|var| (var == "RUDDEN_BRANCH_MAIN").then(|| "trunk".to_string()))?;
+
+        // Assert
+        assert_eq!(config.default_importance(), Importance::Urgent);
+        assert_eq!(config.get("add.default_importance").unwrap().1, &Source::Repo(repo));
+        assert!(config.is_main_branch("trunk"));
+        assert!(!config.is_main_branch("develop"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_value_names_the_key() -> io::Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join(CONFIG_FILE);
+        fs::write(&path, "[show]\nline = \"{id} {title}\"\n")?;
+
+        // Act
+        let error = Config::default().load_repo(&path).unwrap_err();
+
+        // Assert
+        assert_eq!(error.key, "show.line");
+        assert!(error.to_string().starts_with("`show.line` (repo config"));
+        assert!(error.message.contains("{title}"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_unknown_key_is_rejected() {
+        // Act
+        let error = Config::default().set_cli("add.colour", "red").unwrap_err();
+
+        // Assert
+        assert_eq!(error.key, "add.colour");
+        assert_eq!(error.message, "unknown configuration key");
+    }
+
+    #[test]
+    fn test_write_value_round_trips() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let path = dir.path().join("nested").join(CONFIG_FILE);
+
+        // Act
+        write_value(&path, "branch.template", "feature/{id}", Source::Repo(path.clone()))?;
+        write_value(&path, "add.default_importance", "urgent", Source::Repo(path.clone()))?;
+        let mut config = Config::default();
+        config.load_repo(&path)?;
+
+        // Assert
+        assert_eq!(config.branch_template(), "feature/{id}");
+        assert_eq!(config.default_importance(), Importance::Urgent);
+        Ok(())
+    }
+
+    #[test]
+    fn test_lenient_load_skips_invalid_settings() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let user = dir.path().join("user.toml");
+        let repo = dir.path().join("repo.toml");
+        fs::write(&user, "[store]\ndir = \"tasks\"\n[show]\nstyle = \"grid\"\n")?;
+        fs::write(&repo, "[store]\ndir = \"elsewhere\"\n[add]\ndefault_importance = \"urgent\"\n")?;
+
+        // Arrange
+        let mut config = Config::lenient();
+
+        // Act
+        config.load_user(&user)?;
+        config.load_repo(&repo)?;
+        let strict = Config::default().load_repo(&repo);
+
+        // Assert
+        let skipped: Vec<&str> = config.problems().iter().map(|e| e.key.as_str()).collect();
+        assert_eq!(skipped, vec!["show.style", "store.dir"]);
+        assert_eq!(config.default_importance(), Importance::Urgent);
+        assert_eq!(config.store_dir(), Some(dir.path().join("tasks")));
+        assert_eq!(strict.unwrap_err().key, "store.dir");
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolve_user_config_path() {
+        assert_eq!(
+            resolve_user_config_path(None, Some("/home/u".into())),
+            Some(PathBuf::from("/home/u/.config/rudden/config.toml"))
+        );
+    }
+}
@@ -0,0 +1,183 @@
+use std::io;
+use std::path::{Path, PathBuf};
+
+use clap::{Arg, Command, CommandFactory};
+use clap_mangen::Man;
+
+use crate::cli::Cli;
+
+/// Where `rudden gen-docs` writes the Markdown reference, inside its output directory.
+pub const REFERENCE_FILE: &str = "cli.md";
+
+/// Where `rudden gen-docs` writes the man pages, inside its output directory.
+pub const MAN_DIR: &str = "man";
+
+/// The command line as clap sees it, built so usage lines and global options are filled in.
+fn command() -> Command {
+    let mut command = Cli::command().disable_help_subcommand(true);
+    command.build();
+    command
+}
+
+/// Help text as a sentence. Clap drops the final period of one-line doc comments.
+fn sentence(text: &str) -> String {
+    let text = text.trim().replace('\n', " ");
+    if text.is_empty() || text.ends_with('.') { text } else { format!("{}.", text) }
+}
+
+/// How an argument is written on the command line, e.g. `-m, --message <MESSAGE>` or `<TASKS>...`.
+fn signature(arg: &Arg) -> String {
+    let value = arg.get_value_names().map_or_else(|| arg.get_id().as_str().to_uppercase(), |names| names.join(" "));
+    let many = if arg.get_num_args().is_some_and(|range| range.max_values() > 1) { "..." } else { "" };
+    if arg.is_positional() {
+        return format!("<{}>{}", value, many);
+    }
+    let names: Vec<String> = arg
+        .get_short()
+        .map(|short| format!("-{}", short))
+        .into_iter()
+        .chain(arg.get_long().map(|long| format!("--{}", long)))
+        .collect();
+    if arg.get_action().takes_values() {
+        format!("{} <{}>", names.join(", "), value)
+    } else {
+        names.join(", ")
+    }
+}
+
+/// One list item per argument: its signature, help, possible values, default and variable.
+fn arguments(args: &[&Arg]) -> String {
+    let mut output = String::new();
+    for arg in args {
+        let help = arg.get_long_help().or(arg.get_help()).map(|help| sentence(&help.to_string())).unwrap_or_default();
+        output.push_str(&format!("- `{}`: {}", signature(arg), help));
+        let values: Vec<String> = arg
+            .get_possible_values()
+            .iter()
+            .filter(|value| !value.is_hide_set())
+            .map(|value| match value.get_help() {
+                Some(help) => format!("`{}` ({})", value.get_name(), help.to_string().trim_end_matches('.')),
+                None => format!("`{}`", value.get_name()),
+            })
+            .collect();
+        if !values.is_empty() {
+            output.push_str(&format!(" One of {}.", values.join(", ")));
+        }
+        let defaults: Vec<String> = arg.get_default_values().iter().map(|value| value.to_string_lossy().into_owned()).collect();
+        if !defaults.is_empty() && arg.get_action().takes_values() {
+            output.push_str(&format!(" Default: `{}`.", defaults.join(",")));
+        }
+        if let Some(env) = arg.get_env() {
+            output.push_str(&format!(" Environment: `{}`.", env.to_string_lossy()));
+        }
+        output.push('\n');
+    }
+    output
+}
+
+/// Appends a command's section, then its subcommands'. Global options are documented once,
+/// with the top-level command.
+fn section(output: &mut String, command: &Command, top_level: bool) {
+    let visible: Vec<&Arg> = command
+        .get_arguments()
+        .filter(|arg| !arg.is_hide_set() && !matches!(arg.get_id().as_str(), "help" | "version"))
+        .filter(|arg| top_level || !arg.is_global_set())
+        .collect();
+    let (positionals, options): (Vec<&Arg>, Vec<&Arg>) = visible.into_iter().partition(|arg| arg.is_positional());
+
+    if !top_level {
+        output.push_str(&format!("## `{}`\n\n", command.get_bin_name().unwrap_or(command.get_name())));
+    }
+    if let Some(about) = command.get_long_about().or(command.get_about()) {
+        output.push_str(&format!("{}\n\n", sentence(&about.to_string())));
+    }
+    let usage = command.clone().render_usage().to_string();
+    output.push_str(&format!("```text\n{}\n```\n\n", usage.trim_end()));
+    if !positionals.is_empty() {
+        output.push_str(&format!("Arguments:\n\n{}\n", arguments(&positionals)));
+    }
+    if !options.is_empty() {
+        let heading = if top_level { "Global options" } else { "Options" };
+        output.push_str(&format!("{}:\n\n{}\n", heading, arguments(&options)));
+    }
+    for subcommand in command.get_subcommands().filter(|subcommand| !subcommand.is_hide_set()) {
+        section(output, subcommand, false);
+    }
+}
+
+/// The command-line reference in Markdown: every visible command with its usage, arguments
+/// and options, straight from the clap definitions in `cli`.
+pub fn markdown() -> String {
+    let mut output = String::from("# rudden command-line reference\n\n");
+    output.push_str("<!-- Generated by `rudden gen-docs` from src/cli.rs; edit the help text there instead. -->\n\n");
+    section(&mut output, &command(), true);
+    output.trim_end().to_string() + "\n"
+}
+
+/// Writes a man page for rudden and one for each visible subcommand, e.g. `rudden-add.1`,
+/// into `dir`. Returns the pages written, subcommands first.
+pub fn man_pages(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    fn generate(command: Command, dir: &Path, pages: &mut Vec<PathBuf>) -> io::Result<()> {
+        for subcommand in command.get_subcommands().filter(|subcommand| !subcommand.is_hide_set()).cloned() {
+            generate(subcommand, dir, pages)?;
+        }
+        pages.push(Man::new(command).generate_to(dir)?);
+        Ok(())
+    }
+
+    let mut pages = Vec::new();
+    generate(command(), dir, &mut pages)?;
+    Ok(pages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test 1: The reference covers every visible command, and no hidden one
+    #[test]
+    fn test_markdown_reference() {
+        // Arrange
+        let expected = ["## `rudden add`", "## `rudden hooks install`", "## `rudden config set`", "Global options:"];
+
+        // Act
+        let reference = markdown();
+
+        // Assert
+        for heading in expected {
+            assert!(reference.contains(heading), "missing {}", heading);
+        }
+        assert!(reference.contains("- `-m, --message <MESSAGE>`: The task's name."));
+        assert!(!reference.contains("__complete"));
+        assert!(!reference.contains("check-msg"));
+    }
+
+    // Test 2: The checked-in reference is up to date with the command line
+    #[test]
+    fn test_checked_in_reference_is_current() {
+        // Arrange
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("docs").join(REFERENCE_FILE);
+
+        // Act
+        let checked_in = std::fs::read_to_string(path).unwrap();
+
+        // Assert
+        assert!(checked_in == markdown(), "docs/cli.md is out of date; run `cargo run -- gen-docs`");
+    }
+
+    // Test 3: Every visible command gets a man page
+    #[test]
+    fn test_man_pages() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+
+        // Act
+        let pages = man_pages(dir.path()).unwrap();
+
+        // Assert
+        assert_eq!(pages.last().unwrap(), &dir.path().join("rudden.1"));
+        assert!(pages.contains(&dir.path().join("rudden-hooks-install.1")));
+        assert!(!pages.iter().any(|page| page.to_string_lossy().contains("__complete")));
+        assert!(std::fs::read_to_string(dir.path().join("rudden-add.1")).unwrap().contains("Longer notes about the task"));
+    }
+}
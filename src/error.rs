@@ -0,0 +1,116 @@
+use std::fmt;
+use std::io;
+
+use crate::config::ConfigError;
+
+/// Everything that can go wrong in rudden, grouped so callers can match on the kind of failure.
+#[derive(Debug)]
+pub enum Error {
+    /// No task has this ID.
+    NotFound(u32),
+    /// No task's name contains this selector.
+    NoMatch(String),
+    /// A task selector fits several tasks, listed as `(id, name)`.
+    Ambiguous { selector: String, matches: Vec<(u32, String)> },
+    InvalidStatus(String),
+    InvalidImportance(String),
+    InvalidTag(String),
+    /// A `show` query or saved query doesn't parse.
+    InvalidQuery(String),
+    /// Making `id` a subtask of `parent` would make a task its own ancestor.
+    InvalidParent { id: u32, parent: u32 },
+    /// Removing this many tasks needs `--yes`.
+    NeedsConfirmation(usize),
+    /// The task is finished and can't be started again.
+    AlreadyFinished(u32),
+    /// No `.rudden` store exists for a command that needs to write one.
+    StoreNotFound,
+    /// Reading or writing the task list, its metadata or other rudden files failed.
+    Storage(io::Error),
+    /// A git command failed.
+    Git(String),
+    /// Stored data is malformed. `line` is 1-based when known.
+    Parse { line: Option<usize>, message: String },
+    Config(ConfigError),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    /// Process exit code for this kind of failure; `1` is left for unexpected errors
+    /// and `2` for command-line usage errors reported by clap.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::NotFound(_) | Self::NoMatch(_) => 3,
+            Self::InvalidStatus(_)
+            | Self::InvalidImportance(_)
+            | Self::InvalidTag(_)
+            | Self::InvalidQuery(_)
+            | Self::Ambiguous { .. }
+            | Self::InvalidParent { .. }
+            | Self::NeedsConfirmation(_)
+            | Self::AlreadyFinished(_) => 4,
+            Self::StoreNotFound => 5,
+            Self::Config(_) => 6,
+            Self::Parse { .. } => 7,
+            Self::Storage(_) => 8,
+            Self::Git(_) => 9,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound(id) => write!(f, "Task with ID {} not found.", id),
+            Self::NoMatch(selector) => write!(f, "No task matches '{}'.", selector),
+            Self::Ambiguous { selector, matches } => {
+                let matches: Vec<String> = matches.iter().map(|(id, name)| format!("#{} {}", id, name)).collect();
+                write!(f, "'{}' matches several tasks: {}. Use an ID instead.", selector, matches.join(", "))
+            }
+            Self::InvalidStatus(s) => {
+                write!(f, "'{}' is not a valid status. Use 'pending', 'started', or 'finished'.", s)
+            }
+            Self::InvalidImportance(s) => {
+                write!(f, "'{}' is not a valid importance. Use 'normal', 'important', or 'urgent'.", s)
+            }
+            Self::InvalidTag(s) => {
+                write!(f, "'{}' is not a valid tag. Tags can't be empty or contain spaces, commas or semicolons.", s)
+            }
+            Self::InvalidQuery(message) => write!(f, "Invalid query: {}", message),
+            Self::InvalidParent { id, parent } => {
+                write!(f, "Task with ID {} can't be a subtask of task {}, since it would become its own ancestor.", id, parent)
+            }
+            Self::NeedsConfirmation(count) => write!(f, "This would remove {} tasks. Pass --yes to confirm.", count),
+            Self::AlreadyFinished(id) => write!(f, "Task with ID {} is already finished.", id),
+            Self::StoreNotFound => write!(f, "No rudden store found. Run `rudden init` to create one."),
+            Self::Storage(e) => write!(f, "Storage error: {}", e),
+            Self::Git(message) => write!(f, "Git error: {}", message),
+            Self::Parse { line: Some(line), message } => write!(f, "line {}: {}", line, message),
+            Self::Parse { line: None, message } => write!(f, "{}", message),
+            Self::Config(e) => write!(f, "Configuration error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Storage(e) => Some(e),
+            Self::Config(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Self::Storage(e)
+    }
+}
+
+impl From<ConfigError> for Error {
+    fn from(e: ConfigError) -> Self {
+        Self::Config(e)
+    }
+}
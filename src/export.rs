@@ -0,0 +1,349 @@
+use std::collections::HashSet;
+
+use clap::ValueEnum;
+
+use crate::task::{Importance, Status, Task};
+
+/// The formats `rudden export` writes.
+#[derive(ValueEnum, PartialEq, Eq, Debug, Clone, Copy)]
+pub enum ExportFormat {
+    /// todo.txt lines, which `rudden import --from todotxt` reads back.
+    Todotxt,
+    /// A Markdown checklist with subtasks nested under their parents, which
+    /// `rudden import --from markdown` reads back.
+    Markdown,
+    /// A standalone HTML page with a table of the tasks.
+    Html,
+    /// An iCalendar file with one VTODO per task, for calendar and reminder apps.
+    Ics,
+}
+
+/// Writes `tasks` as a document in `format`. `now` stamps the iCalendar entries.
+pub fn export(tasks: &[Task], format: ExportFormat, now: u64) -> String {
+    match format {
+        ExportFormat::Todotxt => tasks.iter().map(|task| format!("{}\n", todotxt_line(task))).collect(),
+        ExportFormat::Markdown => markdown(tasks),
+        ExportFormat::Html => html(tasks),
+        ExportFormat::Ics => ics(tasks, now),
+    }
+}
+
+/// Tasks in list order, each followed by its subtasks, with how deeply it is nested.
+/// Subtasks of tasks that aren't being exported are listed at the top level.
+fn tree(tasks: &[Task]) -> Vec<(usize, &Task)> {
+    fn visit<'a>(task: &'a Task, depth: usize, tasks: &'a [Task], seen: &mut HashSet<u32>, out: &mut Vec<(usize, &'a Task)>) {
+        if !seen.insert(task.id) {
+            return;
+        }
+        out.push((depth, task));
+        for child in tasks.iter().filter(|t| t.parent == Some(task.id)) {
+            visit(child, depth + 1, tasks, seen, out);
+        }
+    }
+
+    let ids: HashSet<u32> = tasks.iter().map(|t| t.id).collect();
+    let mut seen = HashSet::new();
+    let mut out = Vec::with_capacity(tasks.len());
+    for task in tasks.iter().filter(|t| t.parent.is_none_or(|parent| !ids.contains(&parent))) {
+        visit(task, 0, tasks, &mut seen, &mut out);
+    }
+    out
+}
+
+/// Percent-escapes a todo.txt value so it stays one word.
+fn escape_word(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '%' => escaped.push_str("%25"),
+            c if c.is_whitespace() => escaped.extend(c.to_string().bytes().map(|byte| format!("%{:02X}", byte))),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// todo.txt puts the completion date first, and drops the priority of finished tasks,
+/// so theirs is kept as a `pri:` tag instead. It has no started tasks or descriptions,
+/// which are kept as `status:` and `description:` tags.
+fn todotxt_line(task: &Task) -> String {
+    let priority = match task.importance {
+        Importance::Urgent => Some('A'),
+        Importance::Important => Some('B'),
+        Importance::Normal => None,
+    };
+    let mut words = Vec::new();
+    if task.status == Status::Finished {
+        words.push("x".to_string());
+        // A creation date can only follow a completion date.
+        if let Some(finished) = task.finished.or(task.created) {
+            words.push(format_date(finished));
+            words.extend(task.created.map(format_date));
+        }
+    } else {
+        words.extend(priority.map(|priority| format!("({})", priority)));
+        words.extend(task.created.map(format_date));
+    }
+    words.push(task.name.clone());
+    words.extend(task.tags.iter().map(|tag| format!("+{}", tag)));
+    if let (Status::Finished, Some(priority)) = (&task.status, priority) {
+        words.push(format!("pri:{}", priority));
+    }
+    if task.status == Status::Started {
+        words.push("status:started".to_string());
+    }
+    words.extend(task.description.as_deref().map(|description| format!("description:{}", escape_word(description))));
+    words.join(" ")
+}
+
+/// Started tasks are marked `status:started`, and descriptions follow their item,
+/// indented to its text.
+fn markdown(tasks: &[Task]) -> String {
+    let mut output = String::new();
+    for (depth, task) in tree(tasks) {
+        let indent = "  ".repeat(depth);
+        let check = if task.status == Status::Finished { 'x' } else { ' ' };
+        let tags: String = task.tags.iter().map(|tag| format!(" #{}", tag)).collect();
+        let started = if task.status == Status::Started { " status:started" } else { "" };
+        output.push_str(&format!("{}- [{}] {}{}{}\n", indent, check, task.name, tags, started));
+        for line in task.description.iter().flat_map(|description| description.lines()) {
+            match line.trim() {
+                "" => output.push('\n'),
+                _ => output.push_str(&format!("{}  {}\n", indent, line)),
+            }
+        }
+    }
+    output
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;").replace('\'', "&#39;")
+}
+
+fn html(tasks: &[Task]) -> String {
+    let mut rows = String::new();
+    for (depth, task) in tree(tasks) {
+        let description = task.description.as_deref().map(|d| format!("<div class=\"description\">{}</div>", escape_html(d))).unwrap_or_default();
+        rows.push_str(&format!(
+            "<tr class=\"{status}\"><td>{id}</td><td style=\"padding-left: {indent}em\">{name}{description}</td><td>{status}</td><td>{importance}</td><td>{tags}</td><td>{created}</td></tr>\n",
+            status = task.status,
+            id = task.id,
+            indent = 0.5 + depth as f32 * 1.5,
+            name = escape_html(&task.name),
+            importance = task.importance,
+            tags = escape_html(&task.tags.join(", ")),
+            created = task.created.map(format_date).unwrap_or_default(),
+        ));
+    }
+    let finished = tasks.iter().filter(|t| t.status == Status::Finished).count();
+    format!(
+        "<!DOCTYPE html>
+<html lang=\"en\">
+<head>
+<meta charset=\"utf-8\">
+<title>Tasks</title>
+<style>
+body {{ font-family: sans-serif; margin: 2em; }}
+table {{ border-collapse: collapse; }}
+th, td {{ border-bottom: 1px solid #ddd; padding: 0.3em 0.5em; text-align: left; vertical-align: top; }}
+tr.finished td {{ color: #888; text-decoration: line-through; }}
+.description {{ color: #555; font-size: 0.9em; white-space: pre-wrap; }}
+</style>
+</head>
+<body>
+<h1>Tasks</h1>
+<p>{finished} of {total} finished.</p>
+<table>
+<tr><th>ID</th><th>Name</th><th>Status</th><th>Importance</th><th>Tags</th><th>Created</th></tr>
+{rows}</table>
+</body>
+</html>
+",
+        finished = finished,
+        total = tasks.len(),
+        rows = rows,
+    )
+}
+
+/// Escapes text for an iCalendar property value.
+fn escape_ics(text: &str) -> String {
+    text.replace('\\', "\\\\").replace(';', "\\;").replace(',', "\\,").replace("\r\n", "\\n").replace('\n', "\\n")
+}
+
+/// Folds a content line to 75 bytes per line, as iCalendar requires, without splitting characters.
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + 2);
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+/// An iCalendar UTC date-time, like `20240301T123000Z`.
+fn format_datetime(secs: u64) -> String {
+    let time = secs % 86_400;
+    format!("{}T{:02}{:02}{:02}Z", format_date(secs).replace('-', ""), time / 3600, time % 3600 / 60, time % 60)
+}
+
+fn uid(task: &Task) -> String {
+    format!("{}-{}@rudden", task.id, task.created.unwrap_or(0))
+}
+
+/// One VTODO per task. Tasks have no due dates yet, so none is written.
+fn ics(tasks: &[Task], now: u64) -> String {
+    let mut lines = vec!["BEGIN:VCALENDAR".to_string(), "VERSION:2.0".to_string(), "PRODID:-//rudden//rudden//EN".to_string()];
+    for task in tasks {
+        lines.push("BEGIN:VTODO".to_string());
+        lines.push(format!("UID:{}", uid(task)));
+        lines.push(format!("DTSTAMP:{}", format_datetime(now)));
+        lines.push(format!("SUMMARY:{}", escape_ics(&task.name)));
+        if let Some(description) = &task.description {
+            lines.push(format!("DESCRIPTION:{}", escape_ics(description)));
+        }
+        let status = match task.status {
+            Status::Pending => "NEEDS-ACTION",
+            Status::Started => "IN-PROCESS",
+            Status::Finished => "COMPLETED",
+        };
+        lines.push(format!("STATUS:{}", status));
+        // 1 is the highest priority and 9 the lowest; 0 means none.
+        let priority = match task.importance {
+            Importance::Urgent => 1,
+            Importance::Important => 5,
+            Importance::Normal => 0,
+        };
+        lines.push(format!("PRIORITY:{}", priority));
+        if !task.tags.is_empty() {
+            lines.push(format!("CATEGORIES:{}", task.tags.iter().map(|tag| escape_ics(tag)).collect::<Vec<_>>().join(",")));
+        }
+        if let Some(created) = task.created {
+            lines.push(format!("CREATED:{}", format_datetime(created)));
+        }
+        if let Some(finished) = task.finished.filter(|_| task.status == Status::Finished) {
+            lines.push(format!("COMPLETED:{}", format_datetime(finished)));
+        }
+        if let Some(parent) = task.parent.and_then(|parent| tasks.iter().find(|t| t.id == parent)) {
+            lines.push(format!("RELATED-TO:{}", uid(parent)));
+        }
+        lines.push("END:VTODO".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+    lines.iter().map(|line| fold(line)).collect()
+}
+
+/// The date in the proleptic Gregorian calendar for a number of days since 1970-01-01.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Seconds since the Unix epoch as a `YYYY-MM-DD` date in UTC.
+pub fn format_date(secs: u64) -> String {
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::{self, ImportFormat};
+
+    fn tasks() -> Vec<Task> {
+        vec![
+            Task { id: 1, name: "Ship 2.0".to_string(), importance: Importance::Urgent, created: Some(1709251200), tags: vec!["release".to_string()], ..Default::default() },
+            Task {
+                id: 2,
+                name: "Write notes".to_string(),
+                status: Status::Finished,
+                importance: Importance::Important,
+                created: Some(1709251200),
+                finished: Some(1709596800),
+                parent: Some(1),
+                ..Default::default()
+            },
+            Task { id: 3, name: "Tidy <desk> & chair".to_string(), description: Some("a, b; c\nd".to_string()), ..Default::default() },
+            Task { id: 4, name: "Fix login".to_string(), status: Status::Started, description: Some("100% broken\n\n  since 2.0".to_string()), ..Default::default() },
+        ]
+    }
+
+    // Test 1: todo.txt output reads back as the same tasks
+    #[test]
+    fn test_todotxt_round_trip() {
+        // Arrange
+        let tasks = tasks();
+
+        // Act
+        let document = export(&tasks, ExportFormat::Todotxt, 0);
+        let imported = import::parse(&document, ImportFormat::Todotxt).unwrap();
+
+        // Assert
+        assert_eq!(document.lines().nth(1), Some("x 2024-03-05 2024-03-01 Write notes pri:B"));
+        assert_eq!(document.lines().nth(3), Some("Fix login status:started description:100%25%20broken%0A%0A%20%20since%202.0"));
+        assert_eq!(imported.len(), tasks.len());
+        for (task, imported) in tasks.iter().zip(&imported) {
+            assert_eq!((&imported.task.name, &imported.task.status), (&task.name, &task.status));
+            assert_eq!((&imported.task.importance, &imported.task.tags), (&task.importance, &task.tags));
+            assert_eq!((imported.task.created, imported.task.finished), (task.created, task.finished));
+            assert_eq!(imported.task.description, task.description);
+        }
+    }
+
+    // Test 2: Markdown nests subtasks and reads back with the same parents
+    #[test]
+    fn test_markdown_round_trip() {
+        // Arrange
+        let tasks = tasks();
+
+        // Act
+        let document = export(&tasks, ExportFormat::Markdown, 0);
+        let imported = import::parse(&document, ImportFormat::Markdown).unwrap();
+
+        // Assert
+        assert_eq!(
+            document,
+            "- [ ] Ship 2.0 #release\n  - [x] Write notes\n- [ ] Tidy <desk> & chair\n  a, b; c\n  d\n\
+             - [ ] Fix login status:started\n  100% broken\n\n    since 2.0\n"
+        );
+        let parents: Vec<Option<usize>> = imported.iter().map(|t| t.parent).collect();
+        assert_eq!(parents, vec![None, Some(0), None, None]);
+        for (task, imported) in tasks.iter().zip(&imported) {
+            assert_eq!((&imported.task.status, &imported.task.description), (&task.status, &task.description));
+        }
+    }
+
+    // Test 3: HTML and iCalendar escape their special characters
+    #[test]
+    fn test_html_and_ics_escape_text() {
+        // Arrange
+        let tasks = tasks();
+
+        // Act
+        let html = export(&tasks, ExportFormat::Html, 0);
+        let ics = export(&tasks, ExportFormat::Ics, 1709296200);
+
+        // Assert
+        assert!(html.contains("Tidy &lt;desk&gt; &amp; chair"));
+        assert!(html.contains("<p>1 of 4 finished.</p>"));
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ics.contains("DTSTAMP:20240301T123000Z\r\n"));
+        assert!(ics.contains("DESCRIPTION:a\\, b\\; c\\nd\r\n"));
+        assert!(ics.contains("STATUS:COMPLETED\r\nPRIORITY:5\r\n"));
+        assert!(ics.contains("RELATED-TO:1-1709251200@rudden\r\n"));
+        assert_eq!(fold(&"x".repeat(80)), format!("{}\r\n {}\r\n", "x".repeat(75), "x".repeat(5)));
+    }
+}
@@ -0,0 +1,299 @@
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::error::{Error, Result};
+
+/// Whether `path` is the top of a git working tree.
+pub fn is_repo_root(path: &Path) -> bool {
+    path.join(".git").exists()
+}
+
+/// Walks up from `start` to the enclosing git working tree, if any.
+pub fn find_root(start: &Path) -> Option<PathBuf> {
+    start.ancestors().find(|a| is_repo_root(a)).map(Path::to_path_buf)
+}
+
+/// Asks `git rev-parse` for one of the repository's paths, resolved against `repo_path`.
+fn rev_parse_path(repo_path: &Path, args: &[&str]) -> Option<PathBuf> {
+    let output = Command::new("git").arg("rev-parse").args(args).current_dir(repo_path).output().ok()?;
+    let dir = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !dir.is_empty()).then(|| repo_path.join(dir))
+}
+
+/// The repository's git directory. In a worktree or submodule `.git` is a file pointing
+/// elsewhere, so git is asked; when it can't tell, a `.git` directory is taken as is.
+/// `None` outside a repository.
+pub fn git_dir(repo_path: &Path) -> Option<PathBuf> {
+    rev_parse_path(repo_path, &["--git-dir"]).or_else(|| Some(repo_path.join(".git")).filter(|dir| dir.is_dir()))
+}
+
+/// Reads `key`, e.g. `core.commentChar`, from the git config that applies in `repo_path`,
+/// system, global and included files alike. `None` when it's unset or git can't tell.
+pub fn config_value(repo_path: &Path, key: &str) -> Option<String> {
+    let output = Command::new("git").args(["config", "--get", key]).current_dir(repo_path).output().ok()?;
+    let value = String::from_utf8_lossy(&output.stdout).trim_end_matches('\n').to_string();
+    output.status.success().then_some(value)
+}
+
+/// Returns the directory git runs hooks from, which honours `core.hooksPath` and is shared
+/// by a repository's worktrees.
+pub fn hooks_dir(repo_path: &Path) -> PathBuf {
+    rev_parse_path(repo_path, &["--git-path", "hooks"]).unwrap_or_else(|| repo_path.join(".git").join("hooks"))
+}
+
+/// The branch checked out in `repo_path`, or `None` on a detached HEAD or outside a repository.
+pub fn current_branch(repo_path: &Path) -> Option<String> {
+    let output = Command::new("git").args(["symbolic-ref", "--quiet", "--short", "HEAD"]).current_dir(repo_path).output().ok()?;
+    let branch = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !branch.is_empty()).then_some(branch)
+}
+
+/// The character git starts comment lines with, from `core.commentChar`. `auto` and an
+/// unset key both mean `#`.
+pub fn comment_char(repo_path: &Path) -> char {
+    let value = config_value(repo_path, "core.commentChar");
+    value.filter(|value| value != "auto").and_then(|value| value.chars().next()).unwrap_or('#')
+}
+
+/// A branch-level event recovered from the HEAD reflog.
+#[derive(PartialEq, Eq, Debug)]
+pub enum BranchEvent {
+    Commit { branch: String },
+    Merge { from: String, into: String },
+}
+
+/// Where the reflog entries after `last`, the last one a previous run processed, start. If
+/// `last` is gone, e.g. because the reflog was expired, the entries recorded after it do.
+pub fn unprocessed(logs_content: &str, last: Option<&str>) -> usize {
+    let Some(last) = last else {
+        return 0;
+    };
+    let time = |line: &str| line.split('\t').next().and_then(|head| head.split_whitespace().rev().nth(1)).and_then(|t| t.parse::<u64>().ok());
+    let last_time = time(last).unwrap_or(0);
+    let mut offset = 0;
+    let mut after_last = None;
+    let mut after_time = None;
+    for line in logs_content.split_inclusive('\n') {
+        if after_time.is_none() && time(line).is_some_and(|t| t > last_time) {
+            after_time = Some(offset);
+        }
+        offset += line.len();
+        if line.trim_end() == last {
+            after_last = Some(offset);
+        }
+    }
+    after_last.or(after_time).unwrap_or(offset)
+}
+
+/// The reflog's newest entry, to pass to `unprocessed` next time.
+pub fn last_entry(logs_content: &str) -> Option<&str> {
+    logs_content.lines().map(str::trim_end).rfind(|line| !line.is_empty())
+}
+
+/// The branch a merge commit's default message, "Merge branch 'x' into y", merged from.
+fn merged_branch(subject: &str) -> Option<&str> {
+    let rest = subject.strip_prefix("Merge branch '")?;
+    rest.split_once('\'').map(|(from, _)| from)
+}
+
+/// Replays the HEAD reflog, tracking checkouts so each commit and merge is attributed
+/// to the branch it happened on. Entries before the first checkout have no known branch.
+/// A merge concluded with `git commit` after resolving conflicts is logged as
+/// "commit (merge)" and counts as a merge of the branch its message names. Entries before
+/// byte `from` were processed by an earlier run and are only followed for checkouts.
+pub fn branch_events(logs_content: &str, from: usize) -> Vec<BranchEvent> {
+    let mut current: Option<String> = None;
+    let mut events = Vec::new();
+    let mut offset = 0;
+    for line in logs_content.split_inclusive('\n') {
+        let processed = offset < from;
+        offset += line.len();
+        // Reflog lines are "<old> <new> <who> <when>\t<message>".
+        let Some((_, message)) = line.trim_end().split_once('\t') else {
+            continue;
+        };
+        if let Some(moves) = message.strip_prefix("checkout: moving from ") {
+            current = moves.split_once(" to ").map(|(_, to)| to.to_string());
+        } else if processed {
+            continue;
+        } else if let Some(from) = message.strip_prefix("commit (merge): ").and_then(merged_branch) {
+            if let Some(into) = &current {
+                events.push(BranchEvent::Merge { from: from.to_string(), into: into.clone() });
+            }
+        } else if message.starts_with("commit") {
+            if let Some(branch) = &current {
+                events.push(BranchEvent::Commit { branch: branch.clone() });
+            }
+        } else if let Some(merge) = message.strip_prefix("merge ") {
+            if let (Some(into), Some((from, _))) = (&current, merge.split_once(':')) {
+                events.push(BranchEvent::Merge { from: from.to_string(), into: into.clone() });
+            }
+        }
+    }
+    events
+}
+
+/// The full messages of the commits the reflog records from byte `from` on, for the
+/// trailers their bodies carry. Commits git no longer has are skipped, and a reflog git
+/// can't read, e.g. outside a repository, gives none.
+pub fn commit_messages(repo_path: &Path, logs_content: &str, from: usize) -> Vec<String> {
+    let commits: Vec<&str> = logs_content[from..]
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .filter(|(_, message)| message.starts_with("commit"))
+        .filter_map(|(head, _)| head.split_whitespace().nth(1))
+        .collect();
+    if commits.is_empty() {
+        return Vec::new();
+    }
+    // The commits go in on stdin, since a first check can cover the whole reflog.
+    let child = Command::new("git")
+        .args(["log", "--no-walk=unsorted", "--ignore-missing", "--stdin", "--format=%B%x00"])
+        .current_dir(repo_path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn();
+    let Ok(mut child) = child else {
+        return Vec::new();
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        // A failed write shows up as a failed exit below.
+        let _ = stdin.write_all(format!("{}\n", commits.join("\n")).as_bytes());
+    }
+    match child.wait_with_output() {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+            .split('\0')
+            .map(|message| message.trim().to_string())
+            .filter(|message| !message.is_empty())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Checks out `branch`, creating it from HEAD first if it doesn't exist.
+pub fn checkout_branch(repo_path: &Path, branch: &str) -> Result<()> {
+    let git_error = |e: io::Error| Error::Git(format!("couldn't run git: {}", e));
+    let exists = Command::new("git")
+        .args(["rev-parse", "--verify", "--quiet"])
+        .arg(format!("refs/heads/{}", branch))
+        .current_dir(repo_path)
+        .output()
+        .map_err(git_error)?
+        .status
+        .success();
+
+    let mut checkout = Command::new("git");
+    checkout.arg("checkout").current_dir(repo_path);
+    if !exists {
+        checkout.arg("-b");
+    }
+    let output = checkout.arg(branch).output().map_err(git_error)?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::Git(format!("git checkout {} failed: {}", branch, stderr.trim())));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn git(repo: &Path, args: &[&str]) {
+        let status = Command::new("git").args(args).current_dir(repo).output().unwrap().status;
+        assert!(status.success());
+    }
+
+    #[test]
+    fn test_config_value_follows_includes() {
+        // Arrange
+        let dir = tempdir().unwrap();
+        git(dir.path(), &["init", "--quiet"]);
+        fs::write(dir.path().join(".git").join("extra.config"), "[core]\n\tcommentChar = %\n").unwrap();
+        git(dir.path(), &["config", "include.path", "extra.config"]);
+
+        // Act
+        let value = config_value(dir.path(), "core.commentchar");
+
+        // Assert
+        assert_eq!(value.as_deref(), Some("%"));
+        assert_eq!(comment_char(dir.path()), '%');
+        assert_eq!(config_value(dir.path(), "core.hooksPath"), None);
+    }
+
+    #[test]
+    fn test_hooks_dir_defaults_to_dot_git_hooks() {
+        // Arrange
+        let dir = tempdir().unwrap();
+        git(dir.path(), &["init", "--quiet"]);
+
+        // Act
+        let hooks = hooks_dir(dir.path());
+
+        // Assert
+        assert_eq!(hooks, dir.path().join(".git").join("hooks"));
+    }
+
+    #[test]
+    fn test_hooks_dir_respects_hooks_path() {
+        // Arrange
+        let dir = tempdir().unwrap();
+        git(dir.path(), &["init", "--quiet"]);
+        git(dir.path(), &["config", "core.hooksPath", ".githooks"]);
+
+        // Act
+        let hooks = hooks_dir(dir.path());
+
+        // Assert
+        assert_eq!(hooks, dir.path().join(".githooks"));
+    }
+
+    #[test]
+    fn test_branch_events_follow_checkouts() {
+        // Arrange
+        let logs = "a b Dev <d@x> 1 +0000\tcommit (initial): init\n\
+            b c Dev <d@x> 2 +0000\tcheckout: moving from main to task/3-parser\n\
+            c d Dev <d@x> 3 +0000\tcommit: wip parser\n\
+            d c Dev <d@x> 4 +0000\tcheckout: moving from task/3-parser to main\n\
+            c e Dev <d@x> 5 +0000\tmerge task/3-parser: Fast-forward\n\
+            e f Dev <d@x> 6 +0000\tcommit (merge): Merge branch 'task/4-lexer'\n";
+
+        // Act
+        let events = branch_events(logs, 0);
+
+        // Assert
+        assert_eq!(
+            events,
+            vec![
+                BranchEvent::Commit { branch: "task/3-parser".into() },
+                BranchEvent::Merge { from: "task/3-parser".into(), into: "main".into() },
+                BranchEvent::Merge { from: "task/4-lexer".into(), into: "main".into() },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unprocessed_entries() {
+        // Arrange
+        let logs = "a b Dev <d@x> 1 +0000\tcheckout: moving from main to task/2-docs\nb c Dev <d@x> 2 +0000\tcommit: two\nc d Dev <d@x> 3 +0000\tcommit: three\n";
+        let expired = "b c Dev <d@x> 2 +0000\tcommit: two\nc d Dev <d@x> 3 +0000\tcommit: three\n";
+
+        // Act
+        let last = last_entry(logs);
+        let after_two = unprocessed(logs, Some("b c Dev <d@x> 2 +0000\tcommit: two"));
+        let after_gone = unprocessed(expired, Some("a b Dev <d@x> 1 +0000\tcommit: one"));
+
+        // Assert
+        assert_eq!(last, Some("c d Dev <d@x> 3 +0000\tcommit: three"));
+        assert_eq!(&logs[after_two..], "c d Dev <d@x> 3 +0000\tcommit: three\n");
+        assert_eq!(after_gone, 0);
+        assert_eq!(unprocessed(logs, last), logs.len());
+        assert_eq!(unprocessed(logs, None), 0);
+        // The checkout before the new commit still tells which branch it was on.
+        assert_eq!(branch_events(logs, after_two), vec![BranchEvent::Commit { branch: "task/2-docs".into() }]);
+    }
+}
@@ -0,0 +1,261 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::Serialize;
+
+/// Marker written into every hook script rudden manages, so we never touch foreign hooks.
+const MARKER: &str = "# rudden-managed hook";
+/// Suffix given to a pre-existing hook that our script chains to.
+const CHAINED_SUFFIX: &str = ".rudden-chained";
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Hook {
+    PostCommit,
+    PostMerge,
+    CommitMsg,
+    PrepareCommitMsg,
+}
+
+impl Hook {
+    pub const ALL: [Hook; 4] = [Self::PostCommit, Self::PostMerge, Self::CommitMsg, Self::PrepareCommitMsg];
+
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            Self::PostCommit => "post-commit",
+            Self::PostMerge => "post-merge",
+            Self::CommitMsg => "commit-msg",
+            Self::PrepareCommitMsg => "prepare-commit-msg",
+        }
+    }
+
+    fn command(&self) -> &'static str {
+        match self {
+            Self::PostCommit | Self::PostMerge => "rudden check --skip-without-store",
+            Self::CommitMsg => "rudden hooks check-msg \"$1\"",
+            Self::PrepareCommitMsg => "rudden hooks prepare-msg \"$1\" \"$2\"",
+        }
+    }
+
+    fn script(&self) -> String {
+        let name = self.file_name();
+        [
+            "#!/bin/sh".to_string(),
+            format!("{MARKER}: {name}"),
+            format!("# A hook that existed before installation was moved to {name}{CHAINED_SUFFIX} and runs first."),
+            format!("chained=\"$(dirname \"$0\")/{name}{CHAINED_SUFFIX}\""),
+            "if [ -x \"$chained\" ]; then".to_string(),
+            "    \"$chained\" \"$@\" || exit $?".to_string(),
+            "fi".to_string(),
+            "command -v rudden >/dev/null 2>&1 || exit 0".to_string(),
+            self.command().to_string(),
+        ]
+        .join("\n")
+            + "\n"
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HookState {
+    NotInstalled,
+    Installed,
+    /// Installed, running a pre-existing hook before rudden.
+    Chained,
+    /// A hook exists but rudden doesn't manage it.
+    Foreign,
+}
+
+impl fmt::Display for HookState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = match self {
+            Self::NotInstalled => "not installed",
+            Self::Installed => "installed",
+            Self::Chained => "installed (chained with existing hook)",
+            Self::Foreign => "not installed (unmanaged hook present)",
+        };
+        write!(f, "{}", state)
+    }
+}
+
+fn is_managed(path: &Path) -> io::Result<bool> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(content.contains(MARKER)),
+        // Binary hooks can't be ours.
+        Err(e) if e.kind() == io::ErrorKind::InvalidData => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// Reports whether `hook` is installed in `hooks_dir`.
+pub fn state(hooks_dir: &Path, hook: Hook) -> io::Result<HookState> {
+    let path = hooks_dir.join(hook.file_name());
+    if !path.exists() {
+        return Ok(HookState::NotInstalled);
+    }
+    if !is_managed(&path)? {
+        return Ok(HookState::Foreign);
+    }
+    let chained = hooks_dir.join(format!("{}{}", hook.file_name(), CHAINED_SUFFIX));
+    if chained.exists() {
+        Ok(HookState::Chained)
+    } else {
+        Ok(HookState::Installed)
+    }
+}
+
+/// Installs `hook`, moving any foreign hook aside so it keeps running before ours.
+pub fn install(hooks_dir: &Path, hook: Hook) -> io::Result<HookState> {
+    fs::create_dir_all(hooks_dir)?;
+    let path = hooks_dir.join(hook.file_name());
+    let chained = hooks_dir.join(format!("{}{}", hook.file_name(), CHAINED_SUFFIX));
+
+    if path.exists() && !is_managed(&path)? {
+        if chained.exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("can't chain {}: {} already exists", hook.file_name(), chained.display()),
+            ));
+        }
+        fs::rename(&path, &chained)?;
+    }
+
+    fs::write(&path, hook.script())?;
+    make_executable(&path)?;
+    state(hooks_dir, hook)
+}
+
+/// Removes `hook` if rudden manages it, restoring the hook it was chained to.
+pub fn uninstall(hooks_dir: &Path, hook: Hook) -> io::Result<HookState> {
+    let path = hooks_dir.join(hook.file_name());
+    if !path.exists() || !is_managed(&path)? {
+        return state(hooks_dir, hook);
+    }
+    fs::remove_file(&path)?;
+    let chained = hooks_dir.join(format!("{}{}", hook.file_name(), CHAINED_SUFFIX));
+    if chained.exists() {
+        fs::rename(&chained, &path)?;
+    }
+    state(hooks_dir, hook)
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+/// Extracts the task IDs a commit message references as `#<id>`, ignoring git comment lines.
+pub fn referenced_ids(message: &str) -> Vec<u32> {
+    let mut ids = Vec::new();
+    for line in message.lines().filter(|l| !l.starts_with('#')) {
+        for (i, _) in line.match_indices('#') {
+            let digits: String = line[i + 1..].chars().take_while(char::is_ascii_digit).collect();
+            if let Ok(id) = digits.parse::<u32>() {
+                if !ids.contains(&id) {
+                    ids.push(id);
+                }
+            }
+        }
+    }
+    ids
+}
+
+/// Extracts the task IDs a commit message closes with `Closes #<id>` lines, ignoring case.
+pub fn closed_ids(message: &str) -> Vec<u32> {
+    let mut ids = Vec::new();
+    for line in message.lines().map(str::trim) {
+        if !line.get(..8).is_some_and(|prefix| prefix.eq_ignore_ascii_case("closes #")) {
+            continue;
+        }
+        if let Ok(id) = line[8..].parse::<u32>() {
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+    }
+    ids
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_install_fresh_hook() -> io::Result<()> {
+        let dir = tempdir()?;
+
+        // Act
+        let state = install(dir.path(), Hook::PostCommit)?;
+
+        // Assert
+        assert_eq!(state, HookState::Installed);
+        let script = fs::read_to_string(dir.path().join("post-commit"))?;
+        assert!(script.contains("rudden check --skip-without-store"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_install_chains_and_uninstall_restores_existing_hook() -> io::Result<()> {
+        let dir = tempdir()?;
+        let original = "#!/bin/sh\necho original\n";
+        fs::write(dir.path().join("post-merge"), original)?;
+
+        // Act
+        let installed = install(dir.path(), Hook::PostMerge)?;
+        let removed = uninstall(dir.path(), Hook::PostMerge)?;
+
+        // Assert
+        assert_eq!(installed, HookState::Chained);
+        assert_eq!(removed, HookState::Foreign);
+        assert_eq!(fs::read_to_string(dir.path().join("post-merge"))?, original);
+        assert!(!dir.path().join("post-merge.rudden-chained").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_reinstall_does_not_chain_itself() -> io::Result<()> {
+        let dir = tempdir()?;
+
+        // Act
+        install(dir.path(), Hook::CommitMsg)?;
+        let state = install(dir.path(), Hook::CommitMsg)?;
+
+        // Assert
+        assert_eq!(state, HookState::Installed);
+        assert!(!dir.path().join("commit-msg.rudden-chained").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_referenced_ids_skips_comments() {
+        // Arrange
+        let message = "fix: parser (#3)\n\nRefs #12 and #3\n# Closes #99\n";
+
+        // Act
+        let ids = referenced_ids(message);
+
+        // Assert
+        assert_eq!(ids, vec![3, 12]);
+    }
+
+    #[test]
+    fn test_closed_ids_reads_closes_lines() {
+        // Arrange
+        let message = "fix: parser\n\nRefs #4\nCloses #12\ncloses #3\nCloses #12x\n";
+
+        // Act
+        let ids = closed_ids(message);
+
+        // Assert
+        assert_eq!(ids, vec![12, 3]);
+    }
+}
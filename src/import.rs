@@ -0,0 +1,453 @@
+use std::collections::HashMap;
+
+use clap::ValueEnum;
+use serde::Deserialize;
+
+use crate::error::{Error, Result};
+use crate::task::{self, Importance, Status, Task, TaskId};
+
+/// The formats `rudden import` reads.
+#[derive(ValueEnum, PartialEq, Eq, Debug, Clone, Copy)]
+pub enum ImportFormat {
+    /// todo.txt lines: `x` marks finished tasks, `(A)` and `(B)` set the priority, and
+    /// `+project` and `@context` words become tags.
+    Todotxt,
+    /// Markdown checklist items, `- [ ]` or `- [x]`. Nested items become subtasks and
+    /// `#tag` words become tags.
+    Markdown,
+    /// The output of `gh issue list --json number,title,body,state,labels,createdAt`.
+    GhJson,
+    /// A CSV file with a header row, such as `rudden show --format csv` writes.
+    Csv,
+}
+
+/// A task read from an import file, not yet in any list.
+#[derive(PartialEq, Eq, Debug, Default)]
+pub struct ImportedTask {
+    /// The task's ID is ignored; importing gives it the next free one.
+    pub task: Task,
+    /// The index of the imported task this one is a subtask of.
+    pub parent: Option<usize>,
+}
+
+/// Reads every task in `content`. Lines that aren't tasks, such as Markdown headings or
+/// blank lines, are skipped; a malformed task is an error naming its line.
+pub fn parse(content: &str, format: ImportFormat) -> Result<Vec<ImportedTask>> {
+    match format {
+        ImportFormat::Todotxt => Ok(content.lines().filter_map(parse_todotxt).collect()),
+        ImportFormat::Markdown => Ok(parse_markdown(content)),
+        ImportFormat::GhJson => parse_gh_json(content),
+        ImportFormat::Csv => parse_csv(content),
+    }
+}
+
+fn imported(name: String, tags: Vec<String>) -> ImportedTask {
+    ImportedTask { task: Task { name, tags, ..Default::default() }, parent: None }
+}
+
+/// Sets the status, counting a finished task as finished when it was created if the
+/// source doesn't say.
+fn set_status(task: &mut Task, status: Status) {
+    if status == Status::Finished {
+        task.finished = task.finished.or(task.created);
+    }
+    task.status = status;
+}
+
+/// Tags can't contain spaces, commas or semicolons, so `good first issue` becomes `good-first-issue`.
+fn tag_from(label: &str) -> Option<String> {
+    let tag: String = label.trim().chars().map(|c| if c.is_whitespace() || c == ',' || c == ';' { '-' } else { c }).collect();
+    (!tag.is_empty()).then_some(tag)
+}
+
+/// `A` is the highest todo.txt priority; anything below `B` counts as normal.
+fn todotxt_priority(letter: &str) -> Importance {
+    match letter {
+        "A" => Importance::Urgent,
+        "B" => Importance::Important,
+        _ => Importance::Normal,
+    }
+}
+
+fn parse_todotxt(line: &str) -> Option<ImportedTask> {
+    let mut words = line.split_whitespace().peekable();
+    let finished = words.next_if_eq(&"x").is_some();
+    // A finished task has its completion date first, then its creation date.
+    let finished_on = if finished { words.next_if(|w| parse_date(w).is_some()).and_then(parse_date) } else { None };
+    let mut importance = words
+        .next_if(|w| w.len() == 3 && w.starts_with('(') && w.ends_with(')'))
+        .map_or(Importance::Normal, |w| todotxt_priority(&w[1..2]));
+    let created = words.next_if(|w| parse_date(w).is_some()).and_then(parse_date);
+
+    let mut name = Vec::new();
+    let mut tags = Vec::new();
+    let mut status = None;
+    let mut description = None;
+    for word in words {
+        // Finished tasks keep their priority as a `pri:` tag, since todo.txt drops it.
+        if let Some(priority) = word.strip_prefix("pri:") {
+            importance = importance.max(todotxt_priority(priority));
+            continue;
+        }
+        // `rudden export` adds these for what todo.txt has no place for.
+        if let Some(value) = word.strip_prefix("status:").and_then(|value| value.parse::<Status>().ok()) {
+            status = Some(value);
+            continue;
+        }
+        if let Some(value) = word.strip_prefix("description:") {
+            description = Some(task::unescape(value));
+            continue;
+        }
+        match word.strip_prefix('+').or_else(|| word.strip_prefix('@')).and_then(tag_from) {
+            Some(tag) => tags.push(tag),
+            None => name.push(word),
+        }
+    }
+    if name.is_empty() {
+        return None;
+    }
+    let mut task = imported(name.join(" "), tags);
+    task.task.importance = importance;
+    task.task.created = created;
+    task.task.finished = finished_on;
+    task.task.description = description;
+    if finished {
+        set_status(&mut task.task, Status::Finished);
+    } else if let Some(status) = status {
+        set_status(&mut task.task, status);
+    }
+    Some(task)
+}
+
+/// Lines indented under an item, as far as its text, are its description; blank lines
+/// between them are kept.
+fn parse_markdown(content: &str) -> Vec<ImportedTask> {
+    let mut tasks: Vec<ImportedTask> = Vec::new();
+    // The indentation and index of each item enclosing the current one.
+    let mut enclosing: Vec<(usize, usize)> = Vec::new();
+    // Where the last item's text starts and its index, while its description may follow.
+    let mut described: Option<(usize, usize)> = None;
+    let mut blank_lines = 0;
+    for line in content.lines() {
+        let text = line.trim_start();
+        let indent = line.len() - text.len();
+        let item = text.strip_prefix(['-', '*', '+']).and_then(|rest| rest.strip_prefix(' '));
+        let (item, finished) = match item.and_then(|item| item.split_at_checked(3)) {
+            Some(("[ ]", rest)) => (rest, false),
+            Some(("[x]" | "[X]", rest)) => (rest, true),
+            _ => {
+                match described {
+                    Some(_) if text.is_empty() => blank_lines += 1,
+                    Some((text_indent, index)) if item.is_none() && indent >= text_indent => {
+                        let description = tasks[index].task.description.get_or_insert_with(String::new);
+                        if !description.is_empty() {
+                            description.push_str(&"\n".repeat(blank_lines + 1));
+                        }
+                        description.push_str(line.get(text_indent..).unwrap_or(text));
+                        blank_lines = 0;
+                    }
+                    _ => described = None,
+                }
+                continue;
+            }
+        };
+
+        described = None;
+        let mut name = Vec::new();
+        let mut tags = Vec::new();
+        let mut status = None;
+        for word in item.split_whitespace() {
+            if let Some(value) = word.strip_prefix("status:").and_then(|value| value.parse::<Status>().ok()) {
+                status = Some(value);
+                continue;
+            }
+            match word.strip_prefix('#').and_then(tag_from) {
+                Some(tag) => tags.push(tag),
+                None => name.push(word),
+            }
+        }
+        if name.is_empty() {
+            continue;
+        }
+        while enclosing.last().is_some_and(|(outer, _)| *outer >= indent) {
+            enclosing.pop();
+        }
+        let mut task = imported(name.join(" "), tags);
+        task.parent = enclosing.last().map(|(_, index)| *index);
+        if finished {
+            set_status(&mut task.task, Status::Finished);
+        } else if let Some(status) = status {
+            set_status(&mut task.task, status);
+        }
+        described = Some((indent + 2, tasks.len()));
+        blank_lines = 0;
+        enclosing.push((indent, tasks.len()));
+        tasks.push(task);
+    }
+    tasks
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Issue {
+    title: String,
+    #[serde(default)]
+    body: Option<String>,
+    #[serde(default)]
+    state: Option<String>,
+    #[serde(default)]
+    labels: Vec<Label>,
+    #[serde(default)]
+    created_at: Option<String>,
+    #[serde(default)]
+    closed_at: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Label {
+    name: String,
+}
+
+/// Reads a priority label, like `urgent`, `priority: high` or `P1`.
+fn label_importance(label: &str) -> Option<Importance> {
+    let label = label.to_lowercase();
+    let label = label.strip_prefix("priority:").or_else(|| label.strip_prefix("priority/")).unwrap_or(&label).trim();
+    match label {
+        "urgent" | "critical" | "p0" => Some(Importance::Urgent),
+        "important" | "high" | "p1" => Some(Importance::Important),
+        _ => None,
+    }
+}
+
+fn parse_gh_json(content: &str) -> Result<Vec<ImportedTask>> {
+    let issues: Vec<Issue> = serde_json::from_str(content)
+        .map_err(|e| Error::Parse { line: Some(e.line()), message: format!("Invalid GitHub issues JSON: {}", e) })?;
+    let tasks = issues
+        .into_iter()
+        .map(|issue| {
+            let mut importance = Importance::Normal;
+            let mut tags = Vec::new();
+            for label in &issue.labels {
+                match label_importance(&label.name) {
+                    Some(label) => importance = importance.max(label),
+                    None => tags.extend(tag_from(&label.name)),
+                }
+            }
+            let mut task = imported(issue.title.trim().to_string(), tags);
+            task.task.importance = importance;
+            task.task.description = issue.body.filter(|body| !body.trim().is_empty());
+            task.task.created = issue.created_at.as_deref().and_then(parse_timestamp);
+            task.task.finished = issue.closed_at.as_deref().and_then(parse_timestamp);
+            if issue.state.is_some_and(|state| state.eq_ignore_ascii_case("closed")) {
+                set_status(&mut task.task, Status::Finished);
+            }
+            task
+        })
+        .filter(|task| !task.task.name.is_empty())
+        .collect();
+    Ok(tasks)
+}
+
+/// Splits CSV into records of fields, with the 1-based line each record starts on.
+/// Quoted fields may contain separators, doubled quotes and line breaks.
+fn csv_records(content: &str) -> Vec<(usize, Vec<String>)> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut line = 1;
+    let mut start = 1;
+    let mut chars = content.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, quoted) {
+            ('"', true) if chars.next_if_eq(&'"').is_some() => field.push('"'),
+            ('"', true) => quoted = false,
+            ('"', false) if field.is_empty() => quoted = true,
+            (',', false) => record.push(std::mem::take(&mut field)),
+            ('\r', false) => {}
+            ('\n', false) => {
+                record.push(std::mem::take(&mut field));
+                records.push((start, std::mem::take(&mut record)));
+                line += 1;
+                start = line;
+            }
+            ('\n', true) => {
+                field.push(c);
+                line += 1;
+            }
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push((start, record));
+    }
+    records.retain(|(_, record)| record.iter().any(|field| !field.is_empty()));
+    records
+}
+
+fn parse_csv(content: &str) -> Result<Vec<ImportedTask>> {
+    let mut records = csv_records(content).into_iter();
+    let Some((_, header)) = records.next() else { return Ok(Vec::new()) };
+    let column = |name: &str| header.iter().position(|h| h.trim() == name);
+    let name_column = column("name").ok_or_else(|| Error::Parse { line: Some(1), message: "CSV has no 'name' column".to_string() })?;
+    let columns = ["id", "status", "importance", "created", "tags", "parent", "description", "finished"].map(column);
+
+    let mut tasks = Vec::new();
+    // Parents refer to IDs in the file, which are only known once every row is read.
+    let mut ids: HashMap<TaskId, usize> = HashMap::new();
+    let mut parents = Vec::new();
+    for (line, record) in records {
+        let field = |index: Option<usize>| index.and_then(|i| record.get(i)).map(|f| f.trim()).filter(|f| !f.is_empty());
+        let error = |message: String| Error::Parse { line: Some(line), message };
+        let [id, status, importance, created, tags, parent, description, finished] = columns.map(field);
+        let Some(name) = field(Some(name_column)) else { continue };
+
+        let mut task = imported(name.to_string(), tags.map(|tags| tags.split(';').filter_map(tag_from).collect()).unwrap_or_default());
+        if let Some(importance) = importance {
+            task.task.importance = importance.parse().map_err(|e: Error| error(e.to_string()))?;
+        }
+        let time = |value: Option<&str>, what: &str| {
+            value.map(|v| v.parse().map_err(|_| error(format!("Invalid {} time '{}'", what, v)))).transpose()
+        };
+        task.task.created = time(created, "created")?;
+        task.task.finished = time(finished, "finished")?;
+        task.task.description = description.map(str::to_string);
+        if let Some(status) = status {
+            set_status(&mut task.task, status.parse().map_err(|e: Error| error(e.to_string()))?);
+        }
+        if let Some(id) = id.and_then(|id| id.parse().ok()) {
+            ids.insert(id, tasks.len());
+        }
+        parents.push(parent.and_then(|parent| parent.parse::<TaskId>().ok()));
+        tasks.push(task);
+    }
+    for (task, parent) in tasks.iter_mut().zip(parents) {
+        task.parent = parent.and_then(|parent| ids.get(&parent).copied());
+    }
+    Ok(tasks)
+}
+
+/// Days since 1970-01-01 for a date in the proleptic Gregorian calendar.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = i64::from(month);
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// Reads a `YYYY-MM-DD` date as seconds since the Unix epoch, at midnight UTC.
+pub fn parse_date(date: &str) -> Option<u64> {
+    let mut parts = date.splitn(3, '-');
+    let (year, month, day) = (parts.next()?, parts.next()?, parts.next()?);
+    if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+        return None;
+    }
+    let (year, month, day): (i64, u32, u32) = (year.parse().ok()?, month.parse().ok()?, day.parse().ok()?);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    u64::try_from(days_from_civil(year, month, day) * 86_400).ok()
+}
+
+/// Reads an RFC 3339 UTC timestamp like `2024-03-01T12:30:00Z`, as GitHub writes them.
+fn parse_timestamp(timestamp: &str) -> Option<u64> {
+    let (date, time) = timestamp.split_once('T')?;
+    let mut parts = time.trim_end_matches('Z').splitn(3, ':').map(|part| part.get(..2)?.parse::<u64>().ok());
+    let (hours, minutes, seconds) = (parts.next()??, parts.next()??, parts.next().flatten().unwrap_or(0));
+    Some(parse_date(date)? + hours * 3600 + minutes * 60 + seconds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(tasks: &[ImportedTask]) -> Vec<&str> {
+        tasks.iter().map(|t| t.task.name.as_str()).collect()
+    }
+
+    // Test 1: todo.txt lines map priorities, completion, dates and projects
+    #[test]
+    fn test_parse_todotxt() {
+        // Arrange
+        let content = "(A) 2024-03-01 Call the bank +finance @phone\nx 2024-03-05 2024-03-02 Book flights\n\n(C) Tidy desk\n";
+
+        // Act
+        let tasks = parse(content, ImportFormat::Todotxt).unwrap();
+
+        // Assert
+        assert_eq!(names(&tasks), vec!["Call the bank", "Book flights", "Tidy desk"]);
+        assert_eq!(tasks[0].task.importance, Importance::Urgent);
+        assert_eq!(tasks[0].task.created, Some(1709251200));
+        assert_eq!(tasks[0].task.tags, vec!["finance", "phone"]);
+        assert_eq!(tasks[1].task.status, Status::Finished);
+        assert_eq!(tasks[1].task.finished, Some(1709596800));
+        assert_eq!(tasks[2].task.importance, Importance::Normal);
+    }
+
+    // Test 2: Markdown checklists keep nesting as parents and skip other lines
+    #[test]
+    fn test_parse_markdown() {
+        // Arrange
+        let content = "# Release\n\n- [ ] Ship 2.0 #release\n  - [x] Write notes\n  - [ ] Tag it\n- [X] Plan\n- not a task\n";
+
+        // Act
+        let tasks = parse(content, ImportFormat::Markdown).unwrap();
+
+        // Assert
+        assert_eq!(names(&tasks), vec!["Ship 2.0", "Write notes", "Tag it", "Plan"]);
+        assert_eq!(tasks[0].task.tags, vec!["release"]);
+        let parents: Vec<Option<usize>> = tasks.iter().map(|t| t.parent).collect();
+        assert_eq!(parents, vec![None, Some(0), Some(0), None]);
+        assert_eq!(tasks[1].task.status, Status::Finished);
+        assert_eq!(tasks[2].task.status, Status::Pending);
+    }
+
+    // Test 3: GitHub issues map labels to tags and priorities, and closed issues to finished tasks
+    #[test]
+    fn test_parse_gh_json() {
+        // Arrange
+        let content = r#"[
+            {"number": 7, "title": "Crash on start", "state": "OPEN", "body": "Stack trace attached",
+             "labels": [{"name": "bug"}, {"name": "priority: high"}, {"name": "good first issue"}], "createdAt": "2024-03-01T12:30:00Z"},
+            {"number": 8, "title": "Old idea", "state": "CLOSED", "body": "", "labels": []}
+        ]"#;
+
+        // Act
+        let tasks = parse(content, ImportFormat::GhJson).unwrap();
+        let invalid = parse("{", ImportFormat::GhJson);
+
+        // Assert
+        assert_eq!(tasks[0].task.importance, Importance::Important);
+        assert_eq!(tasks[0].task.tags, vec!["bug", "good-first-issue"]);
+        assert_eq!(tasks[0].task.description.as_deref(), Some("Stack trace attached"));
+        assert_eq!(tasks[0].task.created, Some(1709296200));
+        assert_eq!(tasks[1].task.status, Status::Finished);
+        assert_eq!(tasks[1].task.description, None);
+        assert!(matches!(invalid, Err(Error::Parse { .. })));
+    }
+
+    // Test 4: CSV reads rudden's own columns, quoted fields and parents by ID
+    #[test]
+    fn test_parse_csv() {
+        // Arrange
+        let content = "id,name,status,importance,tags,parent,description\n\
+                       4,\"Say \"\"hi\"\", then leave\",finished,urgent,a;b,,\"two\nlines\"\n\
+                       9,Child,pending,normal,,4,\n";
+
+        // Act
+        let tasks = parse(content, ImportFormat::Csv).unwrap();
+        let invalid = parse("name,status\nOops,done\n", ImportFormat::Csv);
+
+        // Assert
+        assert_eq!(names(&tasks), vec!["Say \"hi\", then leave", "Child"]);
+        assert_eq!(tasks[0].task.status, Status::Finished);
+        assert_eq!(tasks[0].task.tags, vec!["a", "b"]);
+        assert_eq!(tasks[0].task.description.as_deref(), Some("two\nlines"));
+        assert_eq!(tasks[1].parent, Some(0));
+        assert_eq!(invalid.err().unwrap().to_string(), "line 2: 'done' is not a valid status. Use 'pending', 'started', or 'finished'.");
+    }
+}
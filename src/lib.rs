@@ -1,60 +1,400 @@
+pub mod branch;
 pub mod cli;
 pub mod commands;
+pub mod completions;
+pub mod config;
+pub mod docs;
+pub mod error;
+pub mod export;
 pub mod task;
 pub mod persistence;
+pub mod query;
+pub mod git;
+pub mod hooks;
+pub mod import;
+pub mod outcome;
+pub mod registry;
+pub mod render;
+pub mod scan;
+pub mod search;
+pub mod serve;
+pub mod shell;
+pub mod select;
+pub mod sort;
+pub mod store;
+pub mod table;
+pub mod tui;
 
+use std::env;
 use std::fs;
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
 
 use clap::Parser;
 
-use crate::cli::{Cli, Mode};
-use crate::task::ToDoList;
+use crate::cli::{BranchArgs, Cli, HooksAction, Mode, ShowArgs};
+use crate::config::{Config, CONFIG_FILE};
+pub use crate::error::{Error, Result};
+use crate::registry::Registry;
+use crate::store::Store;
+use crate::table::ColorChoice;
+use crate::select::TaskSelector;
+use crate::task::{Task, TaskId, ToDoList};
 
 /// The main entry point for the Rudden application logic.
+/// Each error category exits with its own code (see `Error::exit_code`).
 pub fn run() {
     // We use a helper function to easily bubble up errors with `?`.
     if let Err(e) = try_run() {
-        eprintln!("Application error: {}", e);
-        std::process::exit(1);
+        eprintln!("Error: {}", e);
+        std::process::exit(e.exit_code());
     }
 }
 
 /// Helper function to handle the main logic and propagate errors.
-fn try_run() -> Result<(), Box<dyn std::error::Error>> {
+fn try_run() -> Result<()> {
     let cli = Cli::parse();
+    let mut session = Session::open(&cli)?;
+    let output = session.execute(cli)?;
+    print_output(&output)
+}
 
-    let rudden_dir = ".rudden";
-    fs::create_dir_all(rudden_dir)?;
-    let rudden_file_path = format!("{}/.rudden", rudden_dir);
-
-    let mut to_do_list = ToDoList::load(&rudden_file_path)?;
-
-    // The logic of each command now returns a Result<String, String>
-    // which we can handle here.
-    let command_result = match cli.mode {
-        Mode::Add(ref args) => commands::add_task(&mut to_do_list, args),
-        Mode::Update(ref args) => commands::update_task(&mut to_do_list, args),
-        Mode::Rm(ref args) => commands::remove_task(&mut to_do_list, args),
-        Mode::Show => commands::show_tasks(&to_do_list),
-        Mode::Check => commands::check_tasks(&mut to_do_list, &std::env::current_dir()?).map_err(|e| e.to_string()),
-        Mode::Bench(ref args) => commands::run_benchmark(args),
-    };
+/// The store commands run against and its task list, which `rudden shell` reads afresh for
+/// each command it runs.
+pub struct Session {
+    store: Store,
+    store_override: Option<PathBuf>,
+    global: bool,
+    current_dir: PathBuf,
+    /// Git-aware commands work from the repository root, wherever rudden was run from.
+    repo_path: PathBuf,
+    user_config: Option<PathBuf>,
+    to_do_list: ToDoList,
+    /// The store's lock, held from loading the list to saving it. The shell, the TUI and the
+    /// server keep running for a while, so they take it for each command, change or request
+    /// instead.
+    _lock: Option<fs::File>,
+}
+
+impl Session {
+    /// Locates the store `cli` asks for and loads its tasks.
+    pub fn open(cli: &Cli) -> Result<Self> {
+        let current_dir = env::current_dir()?;
+        let user_config = config::user_config_path();
+        let config = Self::base_config(user_config.as_deref(), matches!(cli.mode, Mode::Config(_)))?;
+        let store_override = cli.store.clone().or_else(|| config.store_dir());
+        let store = if cli.global {
+            Store::global()?
+        } else {
+            Store::locate(&current_dir, store_override.as_deref())
+        };
+        let repo_path = git::find_root(&current_dir).unwrap_or_else(|| current_dir.clone());
+        let _lock = if store.exists() && !is_long_running(&cli.mode) { Some(store.lock()?) } else { None };
+        let to_do_list = ToDoList::load(store.tasks_path())?;
+        Ok(Self { store, store_override, global: cli.global, current_dir, repo_path, user_config, to_do_list, _lock })
+    }
+
+    /// The configuration layers that don't depend on the store, lowest precedence first.
+    /// `rudden config` loads them leniently, so it can still fix an invalid setting.
+    fn base_config(user_config: Option<&Path>, lenient: bool) -> Result<Config> {
+        let mut config = if lenient { Config::lenient() } else { Config::default() };
+        if let Some(path) = user_config {
+            config.load_user(path)?;
+        }
+        config.load_env(|var| env::var(var).ok())?;
+        Ok(config)
+    }
+
+    fn repo_config(&self) -> PathBuf {
+        self.store.dir.join(CONFIG_FILE)
+    }
+
+    pub fn store(&self) -> &Store {
+        &self.store
+    }
+
+    pub fn is_global(&self) -> bool {
+        self.global
+    }
 
-    match command_result {
-        Ok(success_message) => {
-            println!("{}", success_message);
-            // Determine if the state needs to be saved.
-            // We don't save on `show` or `bench`.
-            let should_save = !matches!(cli.mode, Mode::Show | Mode::Bench(_));
-            if should_save {
-                to_do_list.save(&rudden_file_path)?;
+    pub fn to_do_list(&self) -> &ToDoList {
+        &self.to_do_list
+    }
+
+    /// Runs one command and saves what it changed, returning its output. The configuration
+    /// is read afresh each time, so `config set` in the shell applies to the next command.
+    pub fn execute(&mut self, mut cli: Cli) -> Result<String> {
+        // A command run from the shell takes the lock itself, and reads the list as other
+        // rudden processes may have left it.
+        let _lock = match &self._lock {
+            None if self.store.exists() && !is_long_running(&cli.mode) => {
+                let lock = self.store.lock()?;
+                self.to_do_list = ToDoList::load(self.store.tasks_path())?;
+                Some(lock)
             }
+            _ => None,
+        };
+        let mut config = Self::base_config(self.user_config.as_deref(), matches!(cli.mode, Mode::Config(_)))?;
+        let repo_config = self.repo_config();
+        config.load_repo(&repo_config)?;
+        match &cli.mode {
+            Mode::Check(args) => apply_branch_flags(&mut config, &args.branch)?,
+            Mode::Start(args) => apply_branch_flags(&mut config, &args.branch)?,
+            Mode::Show(args) => apply_show_flags(&mut config, args)?,
+            Mode::Search(_) => resolve_terminal(&mut config)?,
+            _ => {}
         }
-        Err(error_message) => {
-            eprintln!("Error: {}", error_message);
-            return Err(Box::from(error_message));
+
+        // Commands return typed outcomes; this writes one in the requested format.
+        macro_rules! emit {
+            ($outcome:expr) => {
+                render::emit(&$outcome?, cli.format, cli.quiet, &config)?
+            };
         }
+
+        if let Mode::Init = cli.mode {
+            let output = emit!(commands::init_store(&self.store));
+            // Repository stores are remembered for `rudden dash`.
+            if !self.global {
+                let registry_path = Registry::default_path()?;
+                let mut registry = Registry::load(&registry_path)?;
+                if registry.register(&self.store.dir.canonicalize()?) {
+                    registry.save(&registry_path)?;
+                }
+            }
+            return Ok(output);
+        }
+
+        // Determine if the state needs to be saved.
+        // We don't save on `show`, `search`, `bench`, `hooks`, `commit-msg`, `export`, `dash`, `config`,
+        // `gen-docs` or the completion commands, nor after `shell`, `tui` and `serve`, which save after each
+        // command, change or request.
+        let should_save = !matches!(
+            cli.mode,
+            Mode::Show(_)
+                | Mode::Search(_)
+                | Mode::Bench(_)
+                | Mode::Hooks(_)
+                | Mode::CommitMsg(_)
+                | Mode::Export(_)
+                | Mode::Dash(_)
+                | Mode::Config(_)
+                | Mode::Shell
+                | Mode::Tui
+                | Mode::Serve(_)
+                | Mode::Completions(_)
+                | Mode::Complete
+                | Mode::GenDocs(_)
+        );
+        // The personal store lives outside any repository, so it's created on demand.
+        if should_save && self.global {
+            fs::create_dir_all(&self.store.dir)?;
+        }
+        // The hooks run in every repository they're installed in, with a store or not.
+        let run_by_hook = match &cli.mode {
+            Mode::Check(args) => args.skip_without_store,
+            Mode::Hooks(args) => matches!(args.action, HooksAction::CheckMsg { .. } | HooksAction::PrepareMsg { .. }),
+            _ => false,
+        };
+        if run_by_hook && !self.store.exists() {
+            return Ok(String::new());
+        }
+        if (should_save || is_long_running(&cli.mode)) && !self.store.exists() {
+            return Err(Error::StoreNotFound);
+        }
+
+        // The archive is only read by the commands that need it, so it doesn't slow the rest down.
+        let uses_archive = match &cli.mode {
+            Mode::Archive(_) | Mode::Unarchive(_) => true,
+            Mode::Show(args) => args.archived,
+            Mode::Check(_) => config.archive_after().is_some(),
+            _ => false,
+        };
+        let creates_tasks = matches!(cli.mode, Mode::Add(_) | Mode::Import(_) | Mode::Scan(_) | Mode::Unarchive(_));
+        let mut archive = if uses_archive || creates_tasks { ToDoList::load(self.store.archive_path())? } else { ToDoList::default() };
+        // Archived tasks keep their IDs, so new tasks mustn't get them.
+        if creates_tasks {
+            self.to_do_list.reserve_ids(&archive);
+        }
+        if io::stdin().is_terminal() && io::stderr().is_terminal() {
+            disambiguate(&mut cli.mode, &self.to_do_list)?;
+        }
+
+        let to_do_list = &mut self.to_do_list;
+        let repo_path = &self.repo_path;
+        let mut last_checked = None;
+        let output = match cli.mode {
+            Mode::Add(ref args) => emit!(commands::add_task(to_do_list, args, &config)),
+            Mode::Update(ref args) => emit!(commands::update_task(to_do_list, args, &config)),
+            Mode::Rm(ref args) => emit!(commands::remove_task(to_do_list, args, &config)),
+            Mode::Show(ref args) if args.all => {
+                let repo_store = Store::locate(&self.current_dir, self.store_override.as_deref());
+                let global_store = Store::global()?;
+                let repo_list = ToDoList::load(repo_store.tasks_path())?;
+                let global_list = ToDoList::load(global_store.tasks_path())?;
+                emit!(commands::show_all_tasks(&repo_list, &global_list, args, &config))
+            }
+            Mode::Show(ref args) if args.archived => emit!(commands::show_tasks(&archive, args, &config)),
+            Mode::Show(ref args) => emit!(commands::show_tasks(to_do_list, args, &config)),
+            Mode::Search(ref args) => emit!(commands::search_tasks(to_do_list, args)),
+            Mode::Init => unreachable!("init is handled before the other commands"),
+            Mode::Check(_) => {
+                last_checked = self.store.last_checked()?;
+                emit!(commands::check_tasks(to_do_list, &mut archive, repo_path, &config, &mut last_checked))
+            }
+            Mode::Archive(ref args) => emit!(commands::archive_tasks(to_do_list, &mut archive, args)),
+            Mode::Import(ref args) => emit!(commands::import_tasks(to_do_list, args)),
+            Mode::Export(ref args) => emit!(commands::export_tasks(to_do_list, args, &config)),
+            Mode::Scan(ref args) => emit!(commands::scan_tasks(to_do_list, repo_path, args, &config)),
+            Mode::Tui => {
+                *to_do_list = tui::open(self.store.clone(), &config)?;
+                String::new()
+            }
+            Mode::Shell => {
+                shell::run(self)?;
+                String::new()
+            }
+            Mode::Serve(ref args) => {
+                serve::run(args, self.store.clone(), repo_path.clone(), config)?;
+                String::new()
+            }
+            Mode::Completions(ref args) => completions::script(args.shell),
+            Mode::Complete => completions::task_candidates(to_do_list),
+            Mode::GenDocs(ref args) => emit!(commands::generate_docs(args)),
+            Mode::Unarchive(ref args) => emit!(commands::unarchive_tasks(to_do_list, &mut archive, args)),
+            Mode::Bench(ref args) => emit!(commands::run_benchmark(args)),
+            Mode::Hooks(ref args) => emit!(commands::manage_hooks(to_do_list, repo_path, args, &config)),
+            Mode::CommitMsg(ref args) => emit!(commands::commit_message(to_do_list, args)),
+            Mode::Start(ref args) => emit!(commands::start_task(to_do_list, repo_path, args, &config)),
+            Mode::Dash(ref args) => {
+                let registry = Registry::load(Registry::default_path()?)?;
+                let mut lists = Vec::new();
+                // Stores removed since they were registered are skipped.
+                for store_dir in registry.stores().iter().filter(|dir| dir.is_dir()) {
+                    let name = store_dir.parent().unwrap_or(store_dir).display().to_string();
+                    lists.push((name, ToDoList::load(Store { dir: store_dir.clone() }.tasks_path())?));
+                }
+                emit!(commands::dashboard(&lists, args))
+            }
+            Mode::Config(ref args) => {
+                for problem in config.problems() {
+                    eprintln!("Warning: ignoring {}", problem);
+                }
+                emit!(commands::manage_config(&config, args, &repo_config, self.user_config.as_deref()))
+            }
+        };
+
+        if should_save {
+            // Archive first: if saving the list then fails, moved tasks are in both files rather than neither.
+            if uses_archive {
+                archive.save(self.store.archive_path())?;
+            }
+            self.to_do_list.save(self.store.tasks_path())?;
+            // Last, so a failed save leaves `check` to look at the same entries again.
+            if let Some(entry) = &last_checked {
+                self.store.set_last_checked(entry)?;
+            }
+        }
+        Ok(output)
+    }
+}
+
+/// The shell, the TUI and the server, which run until stopped and lock the store for each
+/// command, change or request rather than throughout.
+pub(crate) fn is_long_running(mode: &Mode) -> bool {
+    matches!(mode, Mode::Shell | Mode::Tui | Mode::Serve(_))
+}
+
+/// Prints a command's output, if any. A closed pipe (e.g. `rudden show -q | head -1`) isn't an error.
+fn print_output(output: &str) -> Result<()> {
+    if output.is_empty() {
+        return Ok(());
+    }
+    match writeln!(io::stdout(), "{}", output) {
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        result => Ok(result?),
+    }
+}
+
+/// Applies `show`'s table flags and resolves `auto` color and width against the terminal.
+fn apply_show_flags(config: &mut Config, args: &ShowArgs) -> Result<()> {
+    if let Some(columns) = &args.columns {
+        config.set_cli("show.columns", columns)?;
+    }
+    if args.table || args.columns.is_some() {
+        config.set_cli("show.style", "table")?;
+    }
+    resolve_terminal(config)
+}
+
+/// Resolves `auto` color and width against the terminal, for commands that print tasks.
+fn resolve_terminal(config: &mut Config) -> Result<()> {
+    let terminal = io::stdout().is_terminal();
+    if config.color() == ColorChoice::Auto {
+        // https://no-color.org: any non-empty NO_COLOR disables color.
+        let no_color = env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+        config.set_cli("show.color", if terminal && !no_color { "always" } else { "never" })?;
     }
+    if config.get("show.width").is_some_and(|(width, _)| width == "auto") && terminal {
+        let width = env::var("COLUMNS")
+            .ok()
+            .and_then(|columns| columns.parse::<usize>().ok())
+            .or_else(|| terminal_size::terminal_size().map(|(width, _)| usize::from(width.0)));
+        if let Some(width) = width {
+            config.set_cli("show.width", &width.to_string())?;
+        }
+    }
+    Ok(())
+}
 
+/// On a terminal, asks which task an ambiguous selector means rather than failing.
+fn disambiguate(mode: &mut Mode, to_do_list: &ToDoList) -> Result<()> {
+    let selectors: Vec<&mut TaskSelector> = match mode {
+        Mode::Update(args) => args.tasks.iter_mut().collect(),
+        Mode::Rm(args) => args.tasks.iter_mut().collect(),
+        Mode::Unarchive(_) => return Ok(()),
+        Mode::Start(args) => vec![&mut args.task],
+        Mode::CommitMsg(args) => args.tasks.iter_mut().collect(),
+        _ => return Ok(()),
+    };
+    // Ranges are meant to match several tasks.
+    for selector in selectors.into_iter().filter(|s| !matches!(s, TaskSelector::Range(..))) {
+        let candidates = select::candidates(to_do_list, selector);
+        if candidates.len() > 1 {
+            *selector = TaskSelector::Id(choose_task(selector, &candidates)?);
+        }
+    }
+    Ok(())
+}
+
+/// Lists the candidates on stderr and reads a choice from stdin.
+fn choose_task(selector: &TaskSelector, candidates: &[&Task]) -> Result<TaskId> {
+    let mut stderr = io::stderr();
+    writeln!(stderr, "'{}' matches several tasks:", selector)?;
+    for (number, task) in candidates.iter().enumerate() {
+        writeln!(stderr, "  {}) #{} {} ({})", number + 1, task.id, task.name, task.status)?;
+    }
+    write!(stderr, "Which one? [1-{}] ", candidates.len())?;
+    stderr.flush()?;
+
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    match answer.trim().parse::<usize>() {
+        Ok(number) if (1..=candidates.len()).contains(&number) => Ok(candidates[number - 1].id),
+        _ => Err(Error::Ambiguous {
+            selector: selector.to_string(),
+            matches: candidates.iter().map(|t| (t.id, t.name.clone())).collect(),
+        }),
+    }
+}
+
+/// Command-line branch flags beat every configuration file.
+fn apply_branch_flags(config: &mut Config, args: &BranchArgs) -> Result<()> {
+    if let Some(template) = &args.branch_template {
+        config.set_cli("branch.template", template)?;
+    }
+    if let Some(main) = &args.main_branch {
+        config.set_cli("branch.main", main)?;
+    }
     Ok(())
 }
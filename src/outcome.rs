@@ -0,0 +1,243 @@
+use std::fmt;
+use std::ops::Range;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde::{Serialize, Serializer};
+
+use crate::hooks::{Hook, HookState};
+use crate::task::{Task, TaskId};
+
+/// Identifies a task in an outcome, with its name for display.
+#[derive(PartialEq, Eq, Debug, Clone, Serialize)]
+pub struct TaskRef {
+    pub id: TaskId,
+    pub name: String,
+}
+
+impl From<&Task> for TaskRef {
+    fn from(task: &Task) -> Self {
+        Self { id: task.id, name: task.name.clone() }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Serialize)]
+pub struct InitOutcome {
+    pub dir: PathBuf,
+    /// False when the store already existed.
+    pub created: bool,
+}
+
+#[derive(PartialEq, Eq, Debug, Serialize)]
+pub struct AddOutcome {
+    pub id: TaskId,
+}
+
+#[derive(PartialEq, Eq, Debug, Serialize)]
+pub struct UpdateOutcome {
+    pub tasks: Vec<TaskRef>,
+}
+
+#[derive(PartialEq, Eq, Debug, Serialize)]
+pub struct RemoveOutcome {
+    pub tasks: Vec<TaskRef>,
+}
+
+/// Which list a task shown by `show --all` comes from.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Origin {
+    Repo,
+    Global,
+}
+
+impl fmt::Display for Origin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Repo => write!(f, "repo"),
+            Self::Global => write!(f, "global"),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Serialize)]
+pub struct ListedTask {
+    /// Only set when several lists are shown together.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub origin: Option<Origin>,
+    #[serde(flatten)]
+    pub task: Task,
+}
+
+/// A section of `show --group-by`.
+#[derive(PartialEq, Eq, Debug, Serialize)]
+pub struct TaskGroup {
+    pub name: String,
+    pub tasks: Vec<ListedTask>,
+}
+
+#[derive(PartialEq, Eq, Debug, Serialize)]
+pub struct ShowOutcome {
+    pub tasks: Vec<ListedTask>,
+    /// The same tasks split into sections, when grouping was asked for.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub groups: Option<Vec<TaskGroup>>,
+}
+
+/// Where a search matched, as character ranges of the original text.
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct Highlights {
+    pub name: Vec<Range<usize>>,
+    pub description: Vec<Range<usize>>,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Serialize)]
+pub struct SearchHit {
+    #[serde(flatten)]
+    pub task: Task,
+    /// Higher is a better match.
+    pub score: u32,
+    #[serde(skip)]
+    pub highlights: Highlights,
+}
+
+#[derive(PartialEq, Eq, Debug, Serialize)]
+pub struct SearchOutcome {
+    /// Best matches first.
+    pub hits: Vec<SearchHit>,
+}
+
+#[derive(PartialEq, Eq, Debug, Serialize)]
+pub struct StartOutcome {
+    pub id: TaskId,
+    /// The branch checked out for the task, if any.
+    pub branch: Option<String>,
+}
+
+#[derive(PartialEq, Eq, Debug, Serialize)]
+pub struct CheckOutcome {
+    /// False when there were no git logs to check.
+    pub repository_found: bool,
+    pub started: Vec<TaskRef>,
+    pub finished: Vec<TaskRef>,
+    /// Tasks moved to the archive because of `check.archive_after`.
+    pub archived: Vec<TaskRef>,
+}
+
+#[derive(PartialEq, Eq, Debug, Serialize)]
+pub struct ArchiveOutcome {
+    pub tasks: Vec<TaskRef>,
+}
+
+#[derive(PartialEq, Eq, Debug, Serialize)]
+pub struct UnarchiveOutcome {
+    /// The tasks as they are back in the list; one may have a new ID if its old one was taken.
+    pub tasks: Vec<TaskRef>,
+}
+
+#[derive(PartialEq, Eq, Debug, Serialize)]
+pub struct ImportOutcome {
+    pub created: Vec<TaskRef>,
+    /// Existing tasks with the same name as an imported one, which was left out.
+    pub skipped: Vec<TaskRef>,
+}
+
+#[derive(PartialEq, Eq, Debug, Serialize)]
+pub struct ScanOutcome {
+    /// How many marker comments were found.
+    pub comments: usize,
+    pub created: Vec<TaskRef>,
+    /// Tasks whose comment moved, changed its text or came back after being finished.
+    pub updated: Vec<TaskRef>,
+    /// Tasks whose comment is gone.
+    pub finished: Vec<TaskRef>,
+}
+
+#[derive(PartialEq, Eq, Debug, Serialize)]
+pub struct ExportOutcome {
+    pub ids: Vec<TaskId>,
+    pub document: String,
+}
+
+#[derive(PartialEq, Eq, Debug, Serialize)]
+pub struct HookStatus {
+    pub hook: Hook,
+    pub state: HookState,
+}
+
+#[derive(PartialEq, Eq, Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum HooksOutcome {
+    NoRepository,
+    States { hooks: Vec<HookStatus> },
+    /// Task IDs a commit message references that don't exist.
+    MessageChecked { unknown: Vec<TaskId> },
+    MessagePrepared,
+}
+
+#[derive(PartialEq, Eq, Debug, Serialize)]
+pub struct CommitMsgOutcome {
+    pub ids: Vec<TaskId>,
+    pub message: String,
+}
+
+#[derive(PartialEq, Eq, Debug, Serialize)]
+pub struct RepoSummary {
+    pub name: String,
+    pub pending: usize,
+    pub started: usize,
+    pub finished: usize,
+    pub normal: usize,
+    pub important: usize,
+    pub urgent: usize,
+}
+
+#[derive(PartialEq, Eq, Debug, Serialize)]
+pub struct DashTask {
+    pub repository: String,
+    #[serde(flatten)]
+    pub task: Task,
+}
+
+#[derive(PartialEq, Eq, Debug, Serialize)]
+pub struct DashOutcome {
+    pub repositories: Vec<RepoSummary>,
+    /// Open tasks across all repositories, most important first.
+    pub top: Vec<DashTask>,
+}
+
+#[derive(PartialEq, Eq, Debug, Serialize)]
+pub struct ConfigEntry {
+    pub key: String,
+    pub value: Option<String>,
+    /// Describes the layer the value came from.
+    pub source: Option<String>,
+}
+
+#[derive(PartialEq, Eq, Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ConfigOutcome {
+    Value { key: String, value: Option<String> },
+    Set { key: String, value: String, path: PathBuf },
+    List { entries: Vec<ConfigEntry> },
+}
+
+#[derive(PartialEq, Eq, Debug, Serialize)]
+pub struct DocsOutcome {
+    pub reference: PathBuf,
+    pub man_pages: Vec<PathBuf>,
+}
+
+#[derive(PartialEq, Eq, Debug, Serialize)]
+pub struct BenchOutcome {
+    pub tasks: u32,
+    #[serde(rename = "slow_secs", serialize_with = "secs")]
+    pub slow: Duration,
+    #[serde(rename = "fast_secs", serialize_with = "secs")]
+    pub fast: Duration,
+}
+
+/// Durations are written as fractional seconds rather than serde's `{ secs, nanos }`.
+fn secs<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(duration.as_secs_f64())
+}
@@ -3,6 +3,7 @@ use std::io::{self, Write};
 use std::path::Path;
 use serde::{Deserialize, Serialize};
 
+use crate::error::{Error, Result};
 use crate::task::{Task, Importance, Status};
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -10,28 +11,29 @@ struct Metadata {
     last_id: u32,
 }
 
-fn read_metadata(meta_path: &Path) -> io::Result<Metadata> {
+fn read_metadata(meta_path: &Path) -> Result<Metadata> {
     if !meta_path.exists() {
         return Ok(Metadata { last_id: 0 });
     }
     let file = File::open(meta_path)?;
-    let meta: Metadata = serde_json::from_reader(file)
-        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    let meta: Metadata = serde_json::from_reader(file).map_err(|e| match e.classify() {
+        serde_json::error::Category::Io => Error::Storage(e.into()),
+        _ => Error::Parse { line: Some(e.line()), message: format!("{}: {}", meta_path.display(), e) },
+    })?;
     Ok(meta)
 }
 
-fn write_metadata(meta_path: &Path, meta: &Metadata) -> io::Result<()> {
+fn write_metadata(meta_path: &Path, meta: &Metadata) -> Result<()> {
     let file = OpenOptions::new()
         .write(true)
         .create(true)
         .truncate(true)
         .open(meta_path)?;
-    serde_json::to_writer_pretty(file, meta)
-        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    serde_json::to_writer_pretty(file, meta).map_err(io::Error::from)?;
     Ok(())
 }
 
-fn get_next_id(meta_path: &Path) -> io::Result<u32> {
+fn get_next_id(meta_path: &Path) -> Result<u32> {
     let mut meta = read_metadata(meta_path)?;
     meta.last_id += 1;
     write_metadata(meta_path, &meta)?;
@@ -39,7 +41,7 @@ fn get_next_id(meta_path: &Path) -> io::Result<u32> {
 }
 
 /// Adds a task using an append-only strategy, which is O(1).
-pub fn add_task_fast(data_path: &Path, meta_path: &Path, name: String, importance: Importance) -> io::Result<()> {
+pub fn add_task_fast(data_path: &Path, meta_path: &Path, name: String, importance: Importance) -> Result<()> {
     let mut file = OpenOptions::new().append(true).create(true).open(data_path)?;
     
     let new_id = get_next_id(meta_path)?;
@@ -49,6 +51,7 @@ pub fn add_task_fast(data_path: &Path, meta_path: &Path, name: String, importanc
         name,
         status: Status::Pending,
         importance,
+        ..Task::default()
     };
 
     // This is a simplified `to_csv_line` for direct use.
@@ -63,7 +66,7 @@ pub fn add_task_fast(data_path: &Path, meta_path: &Path, name: String, importanc
 }
 
 /// Clears the data files used by the benchmark.
-pub fn clear_benchmark_data(slow_path: &Path, fast_path: &Path, meta_path: &Path) -> io::Result<()> {
+pub fn clear_benchmark_data(slow_path: &Path, fast_path: &Path, meta_path: &Path) -> Result<()> {
     if slow_path.exists() {
         std::fs::remove_file(slow_path)?;
     }
@@ -84,7 +87,7 @@ mod tests {
     use std::fs;
 
     #[test]
-    fn test_read_metadata_no_file() -> io::Result<()> {
+    fn test_read_metadata_no_file() -> Result<()> {
         let dir = tempdir()?;
         let meta_path = dir.path().join("meta.json");
         
@@ -97,7 +100,7 @@ mod tests {
     }
 
     #[test]
-    fn test_write_and_read_metadata() -> io::Result<()> {
+    fn test_write_and_read_metadata() -> Result<()> {
         let dir = tempdir()?;
         let meta_path = dir.path().join("meta.json");
 
@@ -114,7 +117,7 @@ mod tests {
     }
 
     #[test]
-    fn test_get_next_id_sequentially() -> io::Result<()> {
+    fn test_get_next_id_sequentially() -> Result<()> {
         let dir = tempdir()?;
         let meta_path = dir.path().join("meta.json");
 
@@ -129,7 +132,7 @@ mod tests {
     }
 
     #[test]
-    fn test_add_task_fast_appends_correctly() -> io::Result<()> {
+    fn test_add_task_fast_appends_correctly() -> Result<()> {
         let dir = tempdir()?;
         let data_path = dir.path().join("fast.csv");
         let meta_path = dir.path().join("meta.json");
@@ -148,7 +151,7 @@ mod tests {
     }
 
     #[test]
-    fn test_clear_benchmark_data_removes_files() -> io::Result<()> {
+    fn test_clear_benchmark_data_removes_files() -> Result<()> {
         let dir = tempdir()?;
         let slow_path = dir.path().join("slow.csv");
         let fast_path = dir.path().join("fast.csv");
@@ -168,4 +171,20 @@ mod tests {
         assert!(!meta_path.exists());
         Ok(())
     }
+
+    #[test]
+    fn test_read_metadata_reports_parse_line() -> Result<()> {
+        let dir = tempdir()?;
+        let meta_path = dir.path().join("meta.json");
+
+        // Arrange
+        fs::write(&meta_path, "{\n  \"last_id\": \"seven\"\n}")?;
+
+        // Act
+        let result = read_metadata(&meta_path);
+
+        // Assert
+        assert!(matches!(result, Err(Error::Parse { line: Some(2), .. })));
+        Ok(())
+    }
 }
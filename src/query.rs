@@ -0,0 +1,476 @@
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+use crate::error::{Error, Result};
+use crate::task::{Importance, Status, Task};
+
+/// Saved queries can refer to each other, but not endlessly.
+const MAX_DEPTH: usize = 8;
+
+/// A comparison in a `field<op>value` term.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Op {
+    fn holds(self, ordering: Ordering) -> bool {
+        match self {
+            Self::Eq => ordering == Ordering::Equal,
+            Self::Ne => ordering != Ordering::Equal,
+            Self::Lt => ordering == Ordering::Less,
+            Self::Le => ordering != Ordering::Greater,
+            Self::Gt => ordering == Ordering::Greater,
+            Self::Ge => ordering != Ordering::Less,
+        }
+    }
+}
+
+/// A single test against a task.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum Term {
+    /// Case-insensitive substring of the name; bare words and `"quoted phrases"`.
+    Text(String),
+    Status(Op, Status),
+    Importance(Op, Importance),
+    Tag(Op, String),
+    Id(Op, u32),
+    /// Compares the task's age, in seconds, so `created<7d` means "added in the last week".
+    Age(Op, u64),
+}
+
+/// The parsed form of a query such as `status:pending importance>=important tag:api`.
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub enum Query {
+    /// The empty query.
+    #[default]
+    All,
+    Term(Term),
+    Not(Box<Query>),
+    And(Vec<Query>),
+    Or(Vec<Query>),
+}
+
+impl Query {
+    /// Parses `input`. `@name` refers to a saved query, looked up through `saved`.
+    pub fn parse<'a, F: Fn(&str) -> Option<&'a str>>(input: &str, saved: F) -> Result<Self> {
+        parse_at_depth(input, &saved, 0)
+    }
+
+    /// Whether `task` matches, with `now` in seconds since the Unix epoch.
+    pub fn matches(&self, task: &Task, now: u64) -> bool {
+        match self {
+            Self::All => true,
+            Self::Term(term) => term.matches(task, now),
+            Self::Not(query) => !query.matches(task, now),
+            Self::And(queries) => queries.iter().all(|q| q.matches(task, now)),
+            Self::Or(queries) => queries.iter().any(|q| q.matches(task, now)),
+        }
+    }
+}
+
+impl Term {
+    fn matches(&self, task: &Task, now: u64) -> bool {
+        match self {
+            Self::Text(text) => task.name.to_lowercase().contains(&text.to_lowercase()),
+            Self::Status(op, status) => match op {
+                Op::Ne => task.status != *status,
+                _ => task.status == *status,
+            },
+            Self::Importance(op, importance) => op.holds(task.importance.cmp(importance)),
+            Self::Tag(op, tag) => match op {
+                Op::Ne => !task.tags.contains(tag),
+                _ => task.tags.contains(tag),
+            },
+            Self::Id(op, id) => op.holds(task.id.cmp(id)),
+            // Tasks from before creation times were recorded have no age to compare.
+            Self::Age(op, age) => task.created.is_some_and(|created| op.holds(now.saturating_sub(created).cmp(age))),
+        }
+    }
+}
+
+/// Checks a query's syntax without resolving saved queries, for validating configuration.
+pub fn validate(input: &str) -> std::result::Result<(), String> {
+    match Query::parse(input, |_| Some("")) {
+        Ok(_) => Ok(()),
+        Err(Error::InvalidQuery(message)) => Err(message),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Parses a duration like `30m`, `12h`, `7d` or `2w` into seconds.
+pub fn parse_duration(value: &str) -> Option<u64> {
+    let unit = value.chars().last()?;
+    let amount: u64 = value[..value.len() - unit.len_utf8()].parse().ok()?;
+    let seconds = match unit {
+        'm' => 60,
+        'h' => 3600,
+        'd' => 86_400,
+        'w' => 7 * 86_400,
+        _ => return None,
+    };
+    // Durations too long to count in seconds aren't durations.
+    amount.checked_mul(seconds)
+}
+
+fn invalid(message: String) -> Error {
+    Error::InvalidQuery(message)
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+enum Token {
+    Open,
+    Close,
+    And,
+    Or,
+    Not,
+    Saved(String),
+    Text(String),
+    Field(String, Op, String),
+}
+
+fn describe(token: &Token) -> String {
+    match token {
+        Token::Open => "'('".to_string(),
+        Token::Close => "')'".to_string(),
+        Token::And => "'and'".to_string(),
+        Token::Or => "'or'".to_string(),
+        Token::Not => "'not'".to_string(),
+        Token::Saved(name) => format!("'@{}'", name),
+        Token::Text(text) => format!("'{}'", text),
+        Token::Field(field, _, value) => format!("'{}' term '{}'", field, value),
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::Open);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::Close);
+            }
+            '"' => {
+                chars.next();
+                let phrase: String = chars.by_ref().take_while(|c| *c != '"').collect();
+                tokens.push(Token::Text(phrase));
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || c == '(' || c == ')' {
+                        break;
+                    }
+                    word.push(c);
+                    chars.next();
+                }
+                // `-term` negates the term, as in `-status:finished`.
+                let word = match word.strip_prefix('-') {
+                    Some(rest) if !rest.is_empty() => {
+                        tokens.push(Token::Not);
+                        rest
+                    }
+                    _ => word.as_str(),
+                };
+                tokens.push(classify(word)?);
+            }
+        }
+    }
+    if input.matches('"').count() % 2 == 1 {
+        return Err(invalid("unclosed quote".to_string()));
+    }
+    Ok(tokens)
+}
+
+const FIELDS: [&str; 6] = ["status", "importance", "tag", "name", "id", "created"];
+
+fn classify(word: &str) -> Result<Token> {
+    match word.to_lowercase().as_str() {
+        "and" => return Ok(Token::And),
+        "or" => return Ok(Token::Or),
+        "not" | "-" => return Ok(Token::Not),
+        _ => {}
+    }
+    if let Some(name) = word.strip_prefix('@') {
+        return Ok(Token::Saved(name.to_string()));
+    }
+    let field_len = word.chars().take_while(|c| c.is_ascii_alphabetic()).count();
+    let (field, rest) = word.split_at(field_len);
+    const OPS: [(&str, Op); 7] =
+        [(">=", Op::Ge), ("<=", Op::Le), ("!=", Op::Ne), (":", Op::Eq), ("=", Op::Eq), (">", Op::Gt), ("<", Op::Lt)];
+    for (symbol, op) in OPS {
+        if let Some(value) = rest.strip_prefix(symbol) {
+            if field.is_empty() {
+                break;
+            }
+            // A trailing colon on any other word, as in `feat:`, is part of the text.
+            if op == Op::Eq && value.is_empty() && !FIELDS.contains(&field.to_lowercase().as_str()) {
+                break;
+            }
+            return Ok(Token::Field(field.to_lowercase(), op, value.to_string()));
+        }
+    }
+    Ok(Token::Text(word.to_string()))
+}
+
+fn term(field: &str, op: Op, value: &str) -> Result<Term> {
+    let equality_only = |term: Term| match op {
+        Op::Eq | Op::Ne => Ok(term),
+        _ => Err(invalid(format!("`{}` only supports ':', '=' and '!='", field))),
+    };
+    match field {
+        "status" => equality_only(Term::Status(op, value.parse()?)),
+        "importance" => Ok(Term::Importance(op, value.parse()?)),
+        "tag" => equality_only(Term::Tag(op, value.to_string())),
+        // Names match like bare words; the parser wraps `name!=` in a `not`.
+        "name" => equality_only(Term::Text(value.to_string())),
+        "id" => {
+            let id = value.parse().map_err(|_| invalid(format!("'{}' is not a task ID", value)))?;
+            Ok(Term::Id(op, id))
+        }
+        "created" => {
+            let age = parse_duration(value)
+                .ok_or_else(|| invalid(format!("'{}' is not a duration; use e.g. 30m, 12h, 7d or 2w", value)))?;
+            match op {
+                Op::Eq | Op::Ne => Err(invalid("`created` only supports '<', '<=', '>' and '>='".to_string())),
+                _ => Ok(Term::Age(op, age)),
+            }
+        }
+        _ => Err(invalid(format!(
+            "unknown field `{}`; use status, importance, tag, name, id or created",
+            field
+        ))),
+    }
+}
+
+fn parse_at_depth<'a, F: Fn(&str) -> Option<&'a str>>(input: &str, saved: &F, depth: usize) -> Result<Query> {
+    let tokens = tokenize(input)?;
+    let mut parser = Parser { tokens: &tokens, position: 0, saved, depth };
+    if tokens.is_empty() {
+        return Ok(Query::All);
+    }
+    let query = parser.or()?;
+    match parser.tokens.get(parser.position) {
+        None => Ok(query),
+        Some(Token::Close) => Err(invalid("unmatched ')'".to_string())),
+        Some(token) => Err(invalid(format!("unexpected {}", describe(token)))),
+    }
+}
+
+struct Parser<'t, 'f, F> {
+    tokens: &'t [Token],
+    position: usize,
+    saved: &'f F,
+    depth: usize,
+}
+
+impl<'a, F: Fn(&str) -> Option<&'a str>> Parser<'_, '_, F> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<&Token> {
+        self.position += 1;
+        self.tokens.get(self.position - 1)
+    }
+
+    fn or(&mut self) -> Result<Query> {
+        let mut queries = vec![self.and()?];
+        while self.peek() == Some(&Token::Or) {
+            self.next();
+            queries.push(self.and()?);
+        }
+        Ok(if queries.len() == 1 { queries.remove(0) } else { Query::Or(queries) })
+    }
+
+    fn and(&mut self) -> Result<Query> {
+        let mut queries = vec![self.unary()?];
+        loop {
+            match self.peek() {
+                Some(Token::And) => {
+                    self.next();
+                }
+                None | Some(Token::Or) | Some(Token::Close) => break,
+                Some(_) => {}
+            }
+            queries.push(self.unary()?);
+        }
+        Ok(if queries.len() == 1 { queries.remove(0) } else { Query::And(queries) })
+    }
+
+    fn unary(&mut self) -> Result<Query> {
+        match self.next().cloned() {
+            Some(Token::Not) => Ok(Query::Not(Box::new(self.unary()?))),
+            Some(Token::Open) => {
+                let query = self.or()?;
+                match self.next() {
+                    Some(Token::Close) => Ok(query),
+                    _ => Err(invalid("missing ')'".to_string())),
+                }
+            }
+            Some(Token::Text(text)) => Ok(Query::Term(Term::Text(text))),
+            Some(Token::Field(field, op, value)) => match term(&field, op, &value)? {
+                text @ Term::Text(_) if op == Op::Ne => Ok(Query::Not(Box::new(Query::Term(text)))),
+                term => Ok(Query::Term(term)),
+            },
+            Some(Token::Saved(name)) => {
+                if self.depth >= MAX_DEPTH {
+                    return Err(invalid(format!("saved query @{} refers back to itself", name)));
+                }
+                let text = (self.saved)(&name).ok_or_else(|| {
+                    invalid(format!("no saved query @{}; define it with `rudden config set query.{} ...`", name, name))
+                })?;
+                parse_at_depth(text, self.saved, self.depth + 1)
+            }
+            Some(token) => Err(invalid(format!("unexpected {}", describe(&token)))),
+            None => Err(invalid("query ends too early".to_string())),
+        }
+    }
+}
+
+impl FromStr for Query {
+    type Err = Error;
+
+    /// Parses a query that doesn't use saved queries.
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s, |_| None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: u64 = 1_700_000_000;
+
+    fn task(id: u32, name: &str, status: Status, importance: Importance) -> Task {
+        Task { id, name: name.to_string(), status, importance, ..Default::default() }
+    }
+
+    // Test 1: Terms separated by spaces must all match
+    #[test]
+    fn test_parse_terms_into_and() {
+        // Act
+        let query: Query = "status:pending importance>=important tag:api".parse().unwrap();
+
+        // Assert
+        assert_eq!(
+            query,
+            Query::And(vec![
+                Query::Term(Term::Status(Op::Eq, Status::Pending)),
+                Query::Term(Term::Importance(Op::Ge, Importance::Important)),
+                Query::Term(Term::Tag(Op::Eq, "api".to_string())),
+            ])
+        );
+    }
+
+    // Test 2: Importance compares by rank, and phrases and names match case-insensitively
+    #[test]
+    fn test_matches_importance_and_text() {
+        // Arrange
+        let query: Query = "importance>=important \"login page\"".parse().unwrap();
+        let urgent = task(1, "Fix the Login page", Status::Pending, Importance::Urgent);
+        let normal = task(2, "Fix the login page again", Status::Pending, Importance::Normal);
+
+        // Act & Assert
+        assert!(query.matches(&urgent, NOW));
+        assert!(!query.matches(&normal, NOW));
+        assert!(!"name!=login".parse::<Query>().unwrap().matches(&urgent, NOW));
+    }
+
+    // Test 3: or, negation and parentheses combine terms
+    #[test]
+    fn test_or_not_and_parentheses() {
+        // Arrange
+        let query: Query = "-status:finished (tag:api or id>=3)".parse().unwrap();
+        let mut tagged = task(1, "Tagged", Status::Pending, Importance::Normal);
+        tagged.tags.push("api".to_string());
+        let late = task(3, "Late", Status::Started, Importance::Normal);
+        let done = task(4, "Done", Status::Finished, Importance::Normal);
+
+        // Act & Assert
+        assert!(query.matches(&tagged, NOW));
+        assert!(query.matches(&late, NOW));
+        assert!(!query.matches(&done, NOW));
+        assert!(!query.matches(&task(2, "Other", Status::Pending, Importance::Normal), NOW));
+    }
+
+    // Test 4: created compares how long ago a task was added
+    #[test]
+    fn test_created_compares_age() {
+        // Arrange
+        let query: Query = "created<7d".parse().unwrap();
+        let mut recent = task(1, "Recent", Status::Pending, Importance::Normal);
+        recent.created = Some(NOW - 3 * 86_400);
+        let mut old = task(2, "Old", Status::Pending, Importance::Normal);
+        old.created = Some(NOW - 30 * 86_400);
+        let unknown = task(3, "Unknown", Status::Pending, Importance::Normal);
+
+        // Act & Assert
+        assert!(query.matches(&recent, NOW));
+        assert!(!query.matches(&old, NOW));
+        assert!(!query.matches(&unknown, NOW));
+    }
+
+    // Test 5: Saved queries expand and stop on cycles
+    #[test]
+    fn test_saved_queries_expand_and_stop_on_cycles() {
+        // Arrange
+        let saved = |name: &str| match name {
+            "open" => Some("-status:finished"),
+            "loop" => Some("@loop"),
+            _ => None,
+        };
+
+        // Act
+        let open = Query::parse("@open tag:api", saved);
+        let looped = Query::parse("@loop", saved);
+        let missing = Query::parse("@nope", saved);
+
+        // Assert
+        assert!(open.is_ok());
+        assert!(matches!(looped, Err(Error::InvalidQuery(m)) if m.contains("refers back")));
+        assert!(matches!(missing, Err(Error::InvalidQuery(m)) if m.contains("no saved query @nope")));
+    }
+
+    // Test 6: Malformed queries are rejected with a message
+    #[test]
+    fn test_invalid_queries() {
+        assert!(matches!("colour:red".parse::<Query>(), Err(Error::InvalidQuery(_))));
+        assert!(matches!("status>pending".parse::<Query>(), Err(Error::InvalidQuery(_))));
+        assert!(matches!("status:done".parse::<Query>(), Err(Error::InvalidStatus(_))));
+        assert!(matches!("(tag:api".parse::<Query>(), Err(Error::InvalidQuery(_))));
+        assert!(matches!("\"open".parse::<Query>(), Err(Error::InvalidQuery(_))));
+        assert!(matches!("created<99999999999999999w".parse::<Query>(), Err(Error::InvalidQuery(m)) if m.contains("is not a duration")));
+        assert_eq!("".parse::<Query>().unwrap(), Query::All);
+    }
+
+    // Test 7: A word ending in a colon that isn't a field is matched as text
+    #[test]
+    fn test_trailing_colon_is_text() {
+        // Arrange
+        let feature = task(1, "feat: dark mode", Status::Pending, Importance::Normal);
+
+        // Act
+        let query: Query = "feat:".parse().unwrap();
+
+        // Assert
+        assert_eq!(query, Query::Term(Term::Text("feat:".to_string())));
+        assert!(query.matches(&feature, NOW));
+        assert!(matches!("colour:red".parse::<Query>(), Err(Error::InvalidQuery(_))));
+        assert!(matches!("status:".parse::<Query>(), Err(Error::InvalidStatus(_))));
+    }
+}
@@ -0,0 +1,97 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::store;
+
+/// Name of the registry file inside rudden's data directory.
+const REGISTRY_FILE: &str = "repos";
+
+/// The stores `rudden dash` aggregates, one directory per line.
+#[derive(Default, Debug)]
+pub struct Registry {
+    stores: Vec<PathBuf>,
+}
+
+impl Registry {
+    /// Location of the user's registry, next to the personal store.
+    pub fn default_path() -> io::Result<PathBuf> {
+        store::data_dir().map(|dir| dir.join(REGISTRY_FILE)).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, "can't locate the registry: neither XDG_DATA_HOME nor HOME is set")
+        })
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(content) => {
+                let stores = content.lines().filter(|l| !l.trim().is_empty()).map(PathBuf::from).collect();
+                Ok(Self { stores })
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        if let Some(parent) = path.as_ref().parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new().write(true).create(true).truncate(true).open(path)?;
+        for store in &self.stores {
+            writeln!(file, "{}", store.display())?;
+        }
+        Ok(())
+    }
+
+    /// Adds a store directory. Returns false if it was already registered.
+    pub fn register(&mut self, store_dir: &Path) -> bool {
+        if self.stores.iter().any(|s| s == store_dir) {
+            return false;
+        }
+        self.stores.push(store_dir.to_path_buf());
+        true
+    }
+
+    pub fn stores(&self) -> &[PathBuf] {
+        &self.stores
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_register_save_and_load() -> io::Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("data").join(REGISTRY_FILE);
+
+        // Arrange
+        let mut registry = Registry::default();
+
+        // Act
+        let first = registry.register(Path::new("/work/api/.rudden"));
+        let duplicate = registry.register(Path::new("/work/api/.rudden"));
+        registry.save(&path)?;
+        let loaded = Registry::load(&path)?;
+
+        // Assert
+        assert!(first);
+        assert!(!duplicate);
+        assert_eq!(loaded.stores(), &[PathBuf::from("/work/api/.rudden")]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_missing_registry_is_empty() -> io::Result<()> {
+        let dir = tempdir()?;
+
+        // Act
+        let registry = Registry::load(dir.path().join(REGISTRY_FILE))?;
+
+        // Assert
+        assert!(registry.stores().is_empty());
+        Ok(())
+    }
+}
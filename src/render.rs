@@ -0,0 +1,707 @@
+use clap::ValueEnum;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::config::{self, Config};
+use crate::error::Result;
+use crate::outcome::{
+    AddOutcome, ArchiveOutcome, BenchOutcome, CheckOutcome, CommitMsgOutcome, ConfigOutcome, DashOutcome, DocsOutcome, ExportOutcome, HooksOutcome, ImportOutcome, InitOutcome,
+    ListedTask, RemoveOutcome, ScanOutcome, SearchOutcome, ShowOutcome, StartOutcome, TaskRef, UnarchiveOutcome, UpdateOutcome,
+};
+use crate::search;
+use crate::table::{self, ColorChoice};
+use crate::task::{Task, TaskId};
+
+/// How command outcomes are written to stdout.
+#[derive(ValueEnum, PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum Format {
+    /// Human-readable messages.
+    #[default]
+    Text,
+    /// The whole outcome as one JSON document.
+    Json,
+    /// One JSON object per record.
+    Ndjson,
+    /// One CSV row per record, after a header row.
+    Csv,
+}
+
+/// Turns a command outcome into the text printed on success.
+/// An empty string means the command prints nothing.
+pub trait Render: Serialize {
+    fn render(&self, config: &Config) -> String;
+
+    /// The task IDs printed by `--quiet`, one per line.
+    fn ids(&self) -> Vec<TaskId> {
+        Vec::new()
+    }
+
+    /// The rows written by `ndjson` and `csv`. Defaults to the whole outcome as a single row.
+    fn records(&self) -> Vec<Value> {
+        serde_json::to_value(self).map(|value| vec![value]).unwrap_or_default()
+    }
+}
+
+/// Writes an outcome in the requested format. `quiet` wins over the format and prints only task IDs.
+pub fn emit<T: Render>(outcome: &T, format: Format, quiet: bool, config: &Config) -> Result<String> {
+    if quiet {
+        let ids: Vec<String> = outcome.ids().iter().map(ToString::to_string).collect();
+        return Ok(ids.join("\n"));
+    }
+    let output = match format {
+        Format::Text => outcome.render(config),
+        Format::Json => serde_json::to_string_pretty(outcome).map_err(std::io::Error::from)?,
+        Format::Ndjson => {
+            let lines: Vec<String> = outcome.records().iter().map(Value::to_string).collect();
+            lines.join("\n")
+        }
+        Format::Csv => to_csv(&outcome.records()),
+    };
+    Ok(output)
+}
+
+/// Writes records as CSV. Columns are every key seen, in first-seen order; nested objects
+/// become dotted column names and lists are joined with `;`.
+fn to_csv(records: &[Value]) -> String {
+    let rows: Vec<Vec<(String, String)>> = records
+        .iter()
+        .map(|record| {
+            let mut cells = Vec::new();
+            flatten("", record, &mut cells);
+            cells
+        })
+        .collect();
+    let mut columns: Vec<&str> = Vec::new();
+    for (column, _) in rows.iter().flatten() {
+        if !columns.contains(&column.as_str()) {
+            columns.push(column);
+        }
+    }
+    if columns.is_empty() {
+        return String::new();
+    }
+
+    let mut lines = vec![columns.iter().map(|c| csv_field(c)).collect::<Vec<_>>().join(",")];
+    for row in &rows {
+        let cells: Vec<String> = columns
+            .iter()
+            .map(|column| row.iter().find(|(c, _)| c == column).map(|(_, cell)| csv_field(cell)).unwrap_or_default())
+            .collect();
+        lines.push(cells.join(","));
+    }
+    lines.join("\n")
+}
+
+fn flatten(prefix: &str, value: &Value, cells: &mut Vec<(String, String)>) {
+    match value {
+        Value::Object(fields) => {
+            for (key, field) in fields {
+                let column = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+                flatten(&column, field, cells);
+            }
+        }
+        _ => cells.push((if prefix.is_empty() { "value".to_string() } else { prefix.to_string() }, scalar(value))),
+    }
+}
+
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Array(items) => items.iter().map(scalar).collect::<Vec<_>>().join(";"),
+        other => other.to_string(),
+    }
+}
+
+/// Quotes a field when it contains a separator, a quote or a line break.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+impl Render for InitOutcome {
+    fn render(&self, _config: &Config) -> String {
+        if self.created {
+            format!("Initialized rudden store at {}", self.dir.display())
+        } else {
+            format!("Rudden store already exists at {}", self.dir.display())
+        }
+    }
+}
+
+impl Render for AddOutcome {
+    fn render(&self, _config: &Config) -> String {
+        format!("Successfully added task with ID: {}", self.id)
+    }
+
+    fn ids(&self) -> Vec<TaskId> {
+        vec![self.id]
+    }
+}
+
+/// Reports a change to one task as before, and to several as a list of what was touched.
+fn summary(verb: &str, tasks: &[TaskRef]) -> String {
+    match tasks {
+        [] => "No tasks matched.".to_string(),
+        [task] => format!("Successfully {} task with ID: {}", verb, task.id),
+        tasks => {
+            let mut lines = vec![format!("Successfully {} {} tasks:", verb, tasks.len())];
+            lines.extend(tasks.iter().map(|task| format!("- [id: {}] {}", task.id, task.name)));
+            lines.join("\n")
+        }
+    }
+}
+
+fn task_records(tasks: &[TaskRef]) -> Vec<Value> {
+    tasks.iter().filter_map(|task| serde_json::to_value(task).ok()).collect()
+}
+
+impl Render for UpdateOutcome {
+    fn render(&self, _config: &Config) -> String {
+        summary("updated", &self.tasks)
+    }
+
+    fn ids(&self) -> Vec<TaskId> {
+        self.tasks.iter().map(|task| task.id).collect()
+    }
+
+    fn records(&self) -> Vec<Value> {
+        task_records(&self.tasks)
+    }
+}
+
+impl Render for ArchiveOutcome {
+    fn render(&self, _config: &Config) -> String {
+        match self.tasks.as_slice() {
+            [] => "No finished tasks to archive.".to_string(),
+            tasks => summary("archived", tasks),
+        }
+    }
+
+    fn ids(&self) -> Vec<TaskId> {
+        self.tasks.iter().map(|task| task.id).collect()
+    }
+
+    fn records(&self) -> Vec<Value> {
+        task_records(&self.tasks)
+    }
+}
+
+impl Render for UnarchiveOutcome {
+    fn render(&self, _config: &Config) -> String {
+        summary("unarchived", &self.tasks)
+    }
+
+    fn ids(&self) -> Vec<TaskId> {
+        self.tasks.iter().map(|task| task.id).collect()
+    }
+
+    fn records(&self) -> Vec<Value> {
+        task_records(&self.tasks)
+    }
+}
+
+impl Render for ImportOutcome {
+    fn render(&self, _config: &Config) -> String {
+        let mut lines = Vec::new();
+        match self.created.len() {
+            0 => lines.push("No new tasks to import.".to_string()),
+            1 => lines.push("Imported 1 task:".to_string()),
+            n => lines.push(format!("Imported {} tasks:", n)),
+        }
+        lines.extend(self.created.iter().map(|task| format!("- [id: {}] {}", task.id, task.name)));
+        match self.skipped.len() {
+            0 => {}
+            1 => lines.push("Skipped 1 task already in the list:".to_string()),
+            n => lines.push(format!("Skipped {} tasks already in the list:", n)),
+        }
+        lines.extend(self.skipped.iter().map(|task| format!("- [id: {}] {}", task.id, task.name)));
+        lines.join("\n")
+    }
+
+    fn ids(&self) -> Vec<TaskId> {
+        self.created.iter().map(|task| task.id).collect()
+    }
+
+    /// One row per imported task, existing ones marked as skipped.
+    fn records(&self) -> Vec<Value> {
+        let created = self.created.iter().map(|task| ("created", task));
+        let skipped = self.skipped.iter().map(|task| ("skipped", task));
+        created.chain(skipped).map(|(result, task)| json!({ "id": task.id, "name": task.name, "result": result })).collect()
+    }
+}
+
+impl Render for RemoveOutcome {
+    fn render(&self, _config: &Config) -> String {
+        summary("removed", &self.tasks)
+    }
+
+    fn ids(&self) -> Vec<TaskId> {
+        self.tasks.iter().map(|task| task.id).collect()
+    }
+
+    fn records(&self) -> Vec<Value> {
+        task_records(&self.tasks)
+    }
+}
+
+/// Formats one task with the configured `show.line` template.
+fn task_line(config: &Config, task: &Task) -> String {
+    config::fill(
+        config.show_line(),
+        &[
+            ("id", task.id.to_string()),
+            ("name", task.name.clone()),
+            ("status", task.status.to_string()),
+            ("importance", task.importance.to_string()),
+        ],
+    )
+}
+
+/// The task lines of `show`, without its header: a table or one templated line per task.
+fn task_lines(config: &Config, tasks: &[ListedTask]) -> Vec<String> {
+    if config.show_table() {
+        return vec![table::render_table(tasks, config)];
+    }
+    tasks
+        .iter()
+        .map(|listed| {
+            let line = task_line(config, &listed.task);
+            // Put the origin column right after the list marker, if the template has one.
+            match (listed.origin, line.strip_prefix("- ")) {
+                (None, _) => line,
+                (Some(origin), Some(rest)) => format!("- [{}] {}", origin, rest),
+                (Some(origin), None) => format!("[{}] {}", origin, line),
+            }
+        })
+        .collect()
+}
+
+impl Render for ShowOutcome {
+    fn render(&self, config: &Config) -> String {
+        if self.tasks.is_empty() {
+            return config.show_empty().to_string();
+        }
+        if let Some(groups) = &self.groups {
+            let sections: Vec<String> = groups
+                .iter()
+                .map(|group| {
+                    let mut lines = vec![format!("{} ({}):", group.name, group.tasks.len())];
+                    lines.extend(task_lines(config, &group.tasks));
+                    lines.join("\n")
+                })
+                .collect();
+            return sections.join("\n\n");
+        }
+        if config.show_table() {
+            return table::render_table(&self.tasks, config);
+        }
+
+        let mut lines = vec![config::fill(config.show_header(), &[("count", self.tasks.len().to_string())])];
+        lines.extend(task_lines(config, &self.tasks));
+        lines.join("\n")
+    }
+
+    fn ids(&self) -> Vec<TaskId> {
+        self.tasks.iter().map(|listed| listed.task.id).collect()
+    }
+
+    fn records(&self) -> Vec<Value> {
+        self.tasks.iter().filter_map(|listed| serde_json::to_value(listed).ok()).collect()
+    }
+}
+
+/// Marks search matches in bold yellow when color is on.
+const MATCH_START: &str = "\x1b[1;33m";
+const MATCH_END: &str = "\x1b[0m";
+
+impl Render for SearchOutcome {
+    fn render(&self, config: &Config) -> String {
+        let (start, end) = match config.color() {
+            ColorChoice::Always => (MATCH_START, MATCH_END),
+            _ => ("", ""),
+        };
+        let mut lines = vec![match self.hits.len() {
+            0 => return "No tasks match.".to_string(),
+            1 => "Found 1 matching task:".to_string(),
+            n => format!("Found {} matching tasks:", n),
+        }];
+        for hit in &self.hits {
+            let name = search::highlight(&hit.task.name, &hit.highlights.name, start, end);
+            lines.push(task_line(config, &Task { name, ..hit.task.clone() }));
+            // Descriptions are only shown when the match is in them.
+            if let (Some(description), false) = (&hit.task.description, hit.highlights.description.is_empty()) {
+                let description = search::highlight(description, &hit.highlights.description, start, end);
+                lines.extend(description.lines().map(|line| format!("    {}", line)));
+            }
+        }
+        lines.join("\n")
+    }
+
+    fn ids(&self) -> Vec<TaskId> {
+        self.hits.iter().map(|hit| hit.task.id).collect()
+    }
+
+    fn records(&self) -> Vec<Value> {
+        self.hits.iter().filter_map(|hit| serde_json::to_value(hit).ok()).collect()
+    }
+}
+
+impl Render for StartOutcome {
+    fn render(&self, _config: &Config) -> String {
+        match &self.branch {
+            Some(branch) => format!("Started task with ID: {} on branch '{}'", self.id, branch),
+            None => format!("Started task with ID: {}", self.id),
+        }
+    }
+
+    fn ids(&self) -> Vec<TaskId> {
+        vec![self.id]
+    }
+}
+
+impl Render for CheckOutcome {
+    fn render(&self, _config: &Config) -> String {
+        if !self.repository_found {
+            return "No git repository found, can't check logs.".to_string();
+        }
+        let mut lines = Vec::new();
+        if self.started.is_empty() && self.finished.is_empty() {
+            lines.push("No tasks to update from git logs.".to_string());
+        } else {
+            lines.extend(self.finished.iter().map(|task| format!("Task '{}' finished!", task.name)));
+            lines.push("Tasks updated successfully based on git logs.".to_string());
+        }
+        match self.archived.len() {
+            0 => {}
+            1 => lines.push("Archived 1 finished task.".to_string()),
+            n => lines.push(format!("Archived {} finished tasks.", n)),
+        }
+        lines.join("\n")
+    }
+
+    fn ids(&self) -> Vec<TaskId> {
+        self.started.iter().chain(&self.finished).chain(&self.archived).map(|task| task.id).collect()
+    }
+
+    /// One row per status change or archived task.
+    fn records(&self) -> Vec<Value> {
+        let started = self.started.iter().map(|task| ("started", task));
+        let finished = self.finished.iter().map(|task| ("finished", task));
+        let archived = self.archived.iter().map(|task| ("archived", task));
+        started
+            .chain(finished)
+            .chain(archived)
+            .map(|(change, task)| json!({ "id": task.id, "name": task.name, "change": change }))
+            .collect()
+    }
+}
+
+impl Render for HooksOutcome {
+    fn render(&self, _config: &Config) -> String {
+        match self {
+            Self::NoRepository => "No git repository found, can't manage hooks.".to_string(),
+            Self::States { hooks } => {
+                let lines: Vec<String> =
+                    hooks.iter().map(|status| format!("{}: {}", status.hook.file_name(), status.state)).collect();
+                lines.join("\n")
+            }
+            // Hook helpers only speak up about problems, so they don't clutter git's output.
+            Self::MessageChecked { unknown } => {
+                let lines: Vec<String> = unknown
+                    .iter()
+                    .map(|id| format!("Warning: commit message references unknown task #{}.", id))
+                    .collect();
+                lines.join("\n")
+            }
+            Self::MessagePrepared => String::new(),
+        }
+    }
+
+    fn ids(&self) -> Vec<TaskId> {
+        match self {
+            Self::MessageChecked { unknown } => unknown.clone(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn records(&self) -> Vec<Value> {
+        match self {
+            Self::States { hooks } => hooks.iter().filter_map(|status| serde_json::to_value(status).ok()).collect(),
+            Self::MessageChecked { unknown } => unknown.iter().map(|id| json!({ "unknown": id })).collect(),
+            other => serde_json::to_value(other).map(|value| vec![value]).unwrap_or_default(),
+        }
+    }
+}
+
+impl Render for CommitMsgOutcome {
+    fn render(&self, _config: &Config) -> String {
+        self.message.clone()
+    }
+
+    fn ids(&self) -> Vec<TaskId> {
+        self.ids.clone()
+    }
+}
+
+impl Render for ScanOutcome {
+    fn render(&self, _config: &Config) -> String {
+        let mut lines = vec![match self.comments {
+            1 => "Found 1 TODO comment.".to_string(),
+            n => format!("Found {} TODO comments.", n),
+        }];
+        for (verb, tasks) in [("Created", &self.created), ("Updated", &self.updated), ("Finished", &self.finished)] {
+            match tasks.len() {
+                0 => continue,
+                1 => lines.push(format!("{} 1 task:", verb)),
+                n => lines.push(format!("{} {} tasks:", verb, n)),
+            }
+            lines.extend(tasks.iter().map(|task| format!("- [id: {}] {}", task.id, task.name)));
+        }
+        lines.join("\n")
+    }
+
+    fn ids(&self) -> Vec<TaskId> {
+        self.created.iter().chain(&self.updated).chain(&self.finished).map(|task| task.id).collect()
+    }
+
+    /// One row per changed task.
+    fn records(&self) -> Vec<Value> {
+        let created = self.created.iter().map(|task| ("created", task));
+        let updated = self.updated.iter().map(|task| ("updated", task));
+        let finished = self.finished.iter().map(|task| ("finished", task));
+        created
+            .chain(updated)
+            .chain(finished)
+            .map(|(change, task)| json!({ "id": task.id, "name": task.name, "change": change }))
+            .collect()
+    }
+}
+
+impl Render for ExportOutcome {
+    /// The document as is; printing it adds the final line break back.
+    fn render(&self, _config: &Config) -> String {
+        self.document.strip_suffix('\n').unwrap_or(&self.document).to_string()
+    }
+
+    fn ids(&self) -> Vec<TaskId> {
+        self.ids.clone()
+    }
+}
+
+impl Render for DashOutcome {
+    fn render(&self, _config: &Config) -> String {
+        if self.repositories.is_empty() {
+            return "No repositories registered. Run `rudden init` in a repository to add it.".to_string();
+        }
+
+        let mut output = String::from("Repositories:\n");
+        for repo in &self.repositories {
+            output.push_str(&format!(
+                "- {}: {} pending, {} started, {} finished (normal: {}, important: {}, urgent: {})\n",
+                repo.name, repo.pending, repo.started, repo.finished, repo.normal, repo.important, repo.urgent,
+            ));
+        }
+        if !self.top.is_empty() {
+            output.push_str("\nTop open tasks:\n");
+            for entry in &self.top {
+                let task = &entry.task;
+                output.push_str(&format!(
+                    "- [{} id: {}] {} (Status: {}, Importance: {})\n",
+                    entry.repository, task.id, task.name, task.status, task.importance
+                ));
+            }
+        }
+        output.trim_end().to_string()
+    }
+
+    fn ids(&self) -> Vec<TaskId> {
+        self.top.iter().map(|entry| entry.task.id).collect()
+    }
+
+    /// One row per repository; the top tasks are only in the text and JSON output.
+    fn records(&self) -> Vec<Value> {
+        self.repositories.iter().filter_map(|repo| serde_json::to_value(repo).ok()).collect()
+    }
+}
+
+impl Render for ConfigOutcome {
+    fn render(&self, _config: &Config) -> String {
+        match self {
+            Self::Value { value, .. } => value.clone().unwrap_or_default(),
+            Self::Set { key, value, path } => format!("Set {} = \"{}\" in {}", key, value, path.display()),
+            Self::List { entries } => {
+                let lines: Vec<String> = entries
+                    .iter()
+                    .map(|entry| match (&entry.value, &entry.source) {
+                        (Some(value), Some(source)) => format!("{} = \"{}\" ({})", entry.key, value, source),
+                        (Some(value), None) => format!("{} = \"{}\"", entry.key, value),
+                        (None, _) => format!("{} is not set", entry.key),
+                    })
+                    .collect();
+                lines.join("\n")
+            }
+        }
+    }
+
+    fn records(&self) -> Vec<Value> {
+        match self {
+            Self::List { entries } => entries.iter().filter_map(|entry| serde_json::to_value(entry).ok()).collect(),
+            other => serde_json::to_value(other).map(|value| vec![value]).unwrap_or_default(),
+        }
+    }
+}
+
+impl Render for DocsOutcome {
+    fn render(&self, _config: &Config) -> String {
+        let man_dir = self.man_pages.first().and_then(|page| page.parent()).map(|dir| dir.display().to_string()).unwrap_or_default();
+        format!("Wrote {} and {} man pages in {}.", self.reference.display(), self.man_pages.len(), man_dir)
+    }
+}
+
+impl Render for BenchOutcome {
+    fn render(&self, _config: &Config) -> String {
+        format!(
+            "\n--- Benchmark Results ---\n\
+            Tasks Added: {}\n\n\
+            Slow Method (O(N) Read/Write): {:?}\n\
+            Fast Method (O(1) Append-Only): {:?}\n\n\
+            Conclusion: The append-only method was {:.2}x faster.",
+            self.tasks,
+            self.slow,
+            self.fast,
+            self.slow.as_secs_f64() / self.fast.as_secs_f64()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::outcome::{Origin, TaskGroup};
+    use crate::task::{Importance, Status};
+
+    fn task(id: u32, name: &str) -> Task {
+        Task { id, name: name.to_string(), status: Status::Pending, importance: Importance::Normal, ..Default::default() }
+    }
+
+    // Test 1: Tasks from several lists are marked with their origin
+    #[test]
+    fn test_render_show_with_origins() {
+        // Arrange
+        let outcome = ShowOutcome {
+            tasks: vec![
+                ListedTask { origin: Some(Origin::Repo), task: task(1, "Repo task") },
+                ListedTask { origin: Some(Origin::Global), task: task(1, "Personal task") },
+            ],
+            groups: None,
+        };
+
+        // Act
+        let output = outcome.render(&Config::default());
+
+        // Assert
+        assert_eq!(
+            output,
+            "There are 2 Tasks:\n\
+            - [repo] [id: 1] Repo task (Status: pending, Importance: normal)\n\
+            - [global] [id: 1] Personal task (Status: pending, Importance: normal)"
+        );
+    }
+
+    // Test 2: Finished tasks are announced before the summary line
+    #[test]
+    fn test_render_check_lists_finished_tasks() {
+        // Arrange
+        let outcome = CheckOutcome {
+            repository_found: true,
+            started: Vec::new(),
+            finished: vec![TaskRef { id: 2, name: "fix: Crash".to_string() }],
+            archived: Vec::new(),
+        };
+
+        // Act
+        let output = outcome.render(&Config::default());
+
+        // Assert
+        assert_eq!(output, "Task 'fix: Crash' finished!\nTasks updated successfully based on git logs.");
+    }
+
+    // Test 3: A missing config value renders as nothing
+    #[test]
+    fn test_render_config_value_unset() {
+        // Arrange
+        let outcome = ConfigOutcome::Value { key: "branch.main".to_string(), value: None };
+
+        // Act
+        let output = outcome.render(&Config::default());
+
+        // Assert
+        assert!(output.is_empty());
+    }
+
+    // Test 4: JSON output uses the documented field names and lowercase values
+    #[test]
+    fn test_emit_json_show() {
+        // Arrange
+        let outcome = ShowOutcome { tasks: vec![ListedTask { origin: None, task: task(3, "Write docs") }], groups: None };
+
+        // Act
+        let output = emit(&outcome, Format::Ndjson, false, &Config::default()).unwrap();
+
+        // Assert
+        assert_eq!(output, r#"{"id":3,"name":"Write docs","status":"pending","importance":"normal","created":null,"tags":[],"parent":null,"description":null,"finished":null,"location":null}"#);
+    }
+
+    // Test 5: CSV quotes fields that contain separators or quotes
+    #[test]
+    fn test_emit_csv_quotes_fields() {
+        // Arrange
+        let outcome = ShowOutcome { tasks: vec![ListedTask { origin: None, task: task(1, "Say \"hi\", then leave") }], groups: None };
+
+        // Act
+        let output = emit(&outcome, Format::Csv, false, &Config::default()).unwrap();
+
+        // Assert
+        assert_eq!(output, "id,name,status,importance,created,tags,parent,description,finished,location\n1,\"Say \"\"hi\"\", then leave\",pending,normal,,,,,,");
+    }
+
+    // Test 6: Quiet mode prints only the task IDs, whatever the format
+    #[test]
+    fn test_emit_quiet_prints_ids() {
+        // Arrange
+        let outcome = ShowOutcome {
+            tasks: vec![
+                ListedTask { origin: None, task: task(1, "One") },
+                ListedTask { origin: None, task: task(4, "Four") },
+            ],
+            groups: None,
+        };
+
+        // Act
+        let output = emit(&outcome, Format::Json, true, &Config::default()).unwrap();
+
+        // Assert
+        assert_eq!(output, "1\n4");
+    }
+
+    // Test 7: Grouped tasks print a header with a count for each group
+    #[test]
+    fn test_render_show_groups() {
+        // Arrange
+        let pending = ListedTask { origin: None, task: task(1, "One") };
+        let outcome = ShowOutcome {
+            tasks: vec![pending.clone()],
+            groups: Some(vec![TaskGroup { name: "pending".to_string(), tasks: vec![pending] }]),
+        };
+
+        // Act
+        let output = outcome.render(&Config::default());
+
+        // Assert
+        assert_eq!(output, "pending (1):\n- [id: 1] One (Status: pending, Importance: normal)");
+    }
+}
@@ -0,0 +1,136 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use ignore::WalkBuilder;
+
+use crate::error::{Error, Result};
+use crate::task::Location;
+
+/// The comment markers `rudden scan` turns into tasks.
+pub const MARKERS: [&str; 3] = ["TODO", "FIXME", "HACK"];
+
+/// What a marker must follow to count as a comment rather than, say, a `TODO` in a string.
+const COMMENT_STARTS: [&str; 8] = ["//", "/*", "*", "#", "--", ";", "<!--", "%"];
+
+/// A `TODO`, `FIXME` or `HACK` comment.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Comment {
+    pub location: Location,
+    pub marker: &'static str,
+    /// The comment's text after the marker, or the marker itself if there is none.
+    pub text: String,
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Finds the marker comment on one line, as its marker and text.
+/// Accepts `TODO: text`, `TODO(who): text` and `TODO text`.
+fn comment_in(line: &str) -> Option<(&'static str, String)> {
+    for marker in MARKERS {
+        for (start, _) in line.match_indices(marker) {
+            let before = &line[..start];
+            let after = &line[start + marker.len()..];
+            if before.ends_with(is_word_char) || after.starts_with(is_word_char) {
+                continue;
+            }
+            if !COMMENT_STARTS.iter().any(|comment| before.trim_end().ends_with(comment)) {
+                continue;
+            }
+            let after = match after.strip_prefix('(').and_then(|rest| rest.split_once(')')) {
+                Some((_, rest)) => rest,
+                None => after,
+            };
+            let text = after.trim_start_matches(':').trim().trim_end_matches("*/").trim_end_matches("-->").trim();
+            return Some((marker, if text.is_empty() { marker.to_string() } else { text.to_string() }));
+        }
+    }
+    None
+}
+
+/// Every marker comment in a file's content, with its 1-based line number.
+pub fn comments_in(content: &str) -> Vec<(usize, &'static str, String)> {
+    content
+        .lines()
+        .enumerate()
+        .filter_map(|(index, line)| comment_in(line).map(|(marker, text)| (index + 1, marker, text)))
+        .collect()
+}
+
+/// A path relative to `root`, with `/` separators so locations read the same everywhere.
+pub fn relative(root: &Path, path: &Path) -> String {
+    let path = path.strip_prefix(root).unwrap_or(path);
+    path.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/")
+}
+
+/// Walks `paths`, or all of `root`, for marker comments. Files ignored by `.gitignore`,
+/// hidden files and files that aren't UTF-8 text are skipped.
+pub fn find_comments(root: &Path, paths: &[PathBuf]) -> Result<Vec<Comment>> {
+    let mut paths = paths.iter();
+    let mut walk = WalkBuilder::new(paths.next().map_or(root, PathBuf::as_path));
+    for path in paths {
+        walk.add(path);
+    }
+    walk.require_git(false).sort_by_file_path(Path::cmp);
+
+    let mut comments = Vec::new();
+    for entry in walk.build() {
+        let entry = entry.map_err(|e| Error::Storage(io::Error::other(e.to_string())))?;
+        if !entry.file_type().is_some_and(|kind| kind.is_file()) {
+            continue;
+        }
+        let content = match fs::read_to_string(entry.path()) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::InvalidData => continue,
+            Err(e) => return Err(e.into()),
+        };
+        let path = relative(root, entry.path());
+        for (line, marker, text) in comments_in(&content) {
+            comments.push(Comment { location: Location { path: path.clone(), line }, marker, text });
+        }
+    }
+    Ok(comments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test 1: Markers are found after comment starts, in their usual spellings
+    #[test]
+    fn test_comments_in() {
+        // Arrange
+        let content = "fn main() {\n    // TODO: handle errors\n    let s = \"TODO: not a comment\";\n    # FIXME(ana) flaky on CI\n    /* HACK */\n    // TODOS aren't markers\n}\n";
+
+        // Act
+        let comments = comments_in(content);
+
+        // Assert
+        assert_eq!(
+            comments,
+            vec![(2, "TODO", "handle errors".to_string()), (4, "FIXME", "flaky on CI".to_string()), (5, "HACK", "HACK".to_string())]
+        );
+    }
+
+    // Test 2: Walking skips ignored files and records paths relative to the root
+    #[test]
+    fn test_find_comments_respects_gitignore() {
+        // Arrange
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir(root.path().join("src")).unwrap();
+        fs::write(root.path().join(".gitignore"), "target/\n").unwrap();
+        fs::write(root.path().join("src/lib.rs"), "// TODO: document\n").unwrap();
+        fs::create_dir(root.path().join("target")).unwrap();
+        fs::write(root.path().join("target/gen.rs"), "// TODO: generated\n").unwrap();
+
+        // Act
+        let comments = find_comments(root.path(), &[]).unwrap();
+
+        // Assert
+        assert_eq!(comments.len(), 1);
+        assert_eq!(comments[0].location.to_string(), "src/lib.rs:1");
+        assert_eq!(comments[0].text, "document");
+    }
+}
@@ -0,0 +1,229 @@
+use std::ops::Range;
+
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::outcome::{Highlights, SearchHit};
+use crate::task::Task;
+
+/// Points for a word found in each field. Fuzzy matches score half.
+const NAME_SCORE: u32 = 30;
+const TAG_SCORE: u32 = 20;
+const DESCRIPTION_SCORE: u32 = 10;
+/// Extra points when a word matches at the start of a word rather than inside one.
+const WORD_START_BONUS: u32 = 5;
+
+/// Text lowercased and stripped of accents, remembering where each folded character came from
+/// so matches can be highlighted in the original.
+struct Folded {
+    chars: Vec<char>,
+    /// For each folded character, the index of the original character it came from.
+    origins: Vec<usize>,
+}
+
+impl Folded {
+    fn new(text: &str) -> Self {
+        let mut chars = Vec::new();
+        let mut origins = Vec::new();
+        for (index, c) in text.chars().enumerate() {
+            // Decompose `é` into `e` plus a combining accent, then drop the accent.
+            for folded in c.to_lowercase().nfd().filter(|c| !is_combining_mark(*c)) {
+                chars.push(folded);
+                origins.push(index);
+            }
+        }
+        Self { chars, origins }
+    }
+
+    /// Maps a range of folded characters back to a range of original characters.
+    fn original(&self, range: Range<usize>) -> Range<usize> {
+        self.origins[range.start]..self.origins[range.end - 1] + 1
+    }
+
+    fn is_word_start(&self, index: usize) -> bool {
+        index == 0 || !self.chars[index - 1].is_alphanumeric()
+    }
+
+    /// Every place `term` appears, as ranges of folded characters.
+    fn find_all(&self, term: &[char]) -> Vec<Range<usize>> {
+        if term.is_empty() || term.len() > self.chars.len() {
+            return Vec::new();
+        }
+        (0..=self.chars.len() - term.len())
+            .filter(|start| self.chars[*start..*start + term.len()] == *term)
+            .map(|start| start..start + term.len())
+            .collect()
+    }
+
+    /// The words within a couple of typos of `term`, comparing against whole words and
+    /// against word prefixes of the term's length, so `deplyo` finds `deployment`.
+    fn find_fuzzy(&self, term: &[char]) -> Vec<Range<usize>> {
+        let allowed = match term.len() {
+            0..=3 => return Vec::new(),
+            4..=7 => 1,
+            _ => 2,
+        };
+        let term: String = term.iter().collect();
+        self.words()
+            .into_iter()
+            .filter(|word| {
+                let whole: String = self.chars[word.clone()].iter().collect();
+                let prefix: String = self.chars[word.clone()].iter().take(term.chars().count()).collect();
+                strsim::osa_distance(&term, &whole) <= allowed || strsim::osa_distance(&term, &prefix) <= allowed
+            })
+            .collect()
+    }
+
+    fn words(&self) -> Vec<Range<usize>> {
+        let mut words = Vec::new();
+        let mut start = None;
+        for (index, c) in self.chars.iter().enumerate() {
+            match (c.is_alphanumeric(), start) {
+                (true, None) => start = Some(index),
+                (false, Some(s)) => {
+                    words.push(s..index);
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+        if let Some(s) = start {
+            words.push(s..self.chars.len());
+        }
+        words
+    }
+}
+
+/// How well one search word matches one field: its score and where it matched.
+fn match_field(text: &str, term: &[char], base: u32, fuzzy: bool) -> Option<(u32, Vec<Range<usize>>)> {
+    let folded = Folded::new(text);
+    let (ranges, score) = match folded.find_all(term) {
+        exact if !exact.is_empty() => (exact, base),
+        _ if fuzzy => (folded.find_fuzzy(term), base / 2),
+        _ => return None,
+    };
+    let first = ranges.first()?;
+    let bonus = if folded.is_word_start(first.start) { WORD_START_BONUS } else { 0 };
+    Some((score + bonus, ranges.into_iter().map(|range| folded.original(range)).collect()))
+}
+
+/// Scores `task` against every search word, or `None` if any word doesn't match.
+pub fn score(task: &Task, terms: &[String], fuzzy: bool) -> Option<SearchHit> {
+    let mut total = 0;
+    let mut highlights = Highlights::default();
+    for term in terms {
+        let term = Folded::new(term).chars;
+        let name = match_field(&task.name, &term, NAME_SCORE, fuzzy);
+        let tag = task.tags.iter().filter_map(|tag| match_field(tag, &term, TAG_SCORE, fuzzy)).map(|(s, _)| s).max();
+        let description =
+            task.description.as_deref().and_then(|description| match_field(description, &term, DESCRIPTION_SCORE, fuzzy));
+
+        let best = [name.as_ref().map(|(s, _)| *s), tag, description.as_ref().map(|(s, _)| *s)].into_iter().flatten().max()?;
+        total += best;
+        highlights.name.extend(name.map(|(_, ranges)| ranges).unwrap_or_default());
+        highlights.description.extend(description.map(|(_, ranges)| ranges).unwrap_or_default());
+    }
+    Some(SearchHit { task: task.clone(), score: total, highlights })
+}
+
+/// Finds the tasks matching every word, best first. Equal scores keep their stored order.
+pub fn search(tasks: &[Task], terms: &[String], fuzzy: bool) -> Vec<SearchHit> {
+    let mut hits: Vec<SearchHit> = tasks.iter().filter_map(|task| score(task, terms, fuzzy)).collect();
+    hits.sort_by_key(|hit| std::cmp::Reverse(hit.score));
+    hits
+}
+
+/// Wraps the highlighted character ranges of `text` in `start` and `end`. Overlapping
+/// ranges are merged.
+pub fn highlight(text: &str, ranges: &[Range<usize>], start: &str, end: &str) -> String {
+    let mut marked = vec![false; text.chars().count()];
+    for range in ranges {
+        for flag in marked.iter_mut().take(range.end).skip(range.start) {
+            *flag = true;
+        }
+    }
+    let mut output = String::with_capacity(text.len());
+    let mut inside = false;
+    for (c, marked) in text.chars().zip(marked) {
+        if marked != inside {
+            output.push_str(if marked { start } else { end });
+            inside = marked;
+        }
+        output.push(c);
+    }
+    if inside {
+        output.push_str(end);
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(id: u32, name: &str) -> Task {
+        Task { id, name: name.to_string(), ..Default::default() }
+    }
+
+    fn terms(words: &[&str]) -> Vec<String> {
+        words.iter().map(|w| w.to_string()).collect()
+    }
+
+    // Test 1: Matching ignores case and accents, and highlights the original characters
+    #[test]
+    fn test_search_ignores_case_and_accents() {
+        // Arrange
+        let tasks = vec![task(1, "Fix the Café menu"), task(2, "Order coffee")];
+
+        // Act
+        let hits = search(&tasks, &terms(&["CAFE"]), false);
+
+        // Assert
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].task.id, 1);
+        assert_eq!(highlight(&hits[0].task.name, &hits[0].highlights.name, "[", "]"), "Fix the [Café] menu");
+    }
+
+    // Test 2: Every word must match, and names outrank tags and descriptions
+    #[test]
+    fn test_search_ranks_by_field() {
+        // Arrange
+        let mut described = task(1, "Write notes");
+        described.description = Some("About the login flow".to_string());
+        let mut tagged = task(2, "Polish");
+        tagged.tags = vec!["login".to_string()];
+        let named = task(3, "Login page");
+
+        // Act
+        let hits = search(&[described, tagged, named], &terms(&["login"]), false);
+        let none = search(&[task(4, "Login page")], &terms(&["login", "logout"]), false);
+
+        // Assert
+        let ids: Vec<u32> = hits.iter().map(|hit| hit.task.id).collect();
+        assert_eq!(ids, vec![3, 2, 1]);
+        assert!(none.is_empty());
+    }
+
+    // Test 3: Fuzzy mode tolerates typos, ranking exact matches first
+    #[test]
+    fn test_search_fuzzy() {
+        // Arrange
+        let tasks = vec![task(1, "Deployment script"), task(2, "Deploy docs")];
+
+        // Act
+        let exact = search(&tasks, &terms(&["deplyo"]), false);
+        let fuzzy = search(&tasks, &terms(&["deplyo"]), true);
+
+        // Assert
+        assert!(exact.is_empty());
+        assert_eq!(fuzzy.len(), 2);
+        assert_eq!(highlight(&fuzzy[0].task.name, &fuzzy[0].highlights.name, "[", "]"), "[Deployment] script");
+    }
+
+    // Test 4: Overlapping highlights are merged
+    #[test]
+    fn test_highlight_merges_ranges() {
+        assert_eq!(highlight("abcdef", &[1..3, 2..4], "<", ">"), "a<bcd>ef");
+        assert_eq!(highlight("abc", &[], "<", ">"), "abc");
+    }
+}
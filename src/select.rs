@@ -0,0 +1,192 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::{Error, Result};
+use crate::query::Query;
+use crate::task::{self, Task, TaskId, ToDoList};
+
+/// How a command names the task it works on.
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub enum TaskSelector {
+    /// `12` or `#12`.
+    Id(TaskId),
+    /// `3-9`: every existing task with an ID in the range, inclusive.
+    Range(TaskId, TaskId),
+    /// The most recently added task.
+    #[default]
+    Last,
+    /// A case-insensitive part of the task's name, which must pick out a single task.
+    Name(String),
+}
+
+impl FromStr for TaskSelector {
+    type Err = String;
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let id = s.strip_prefix('#').unwrap_or(s);
+        if let Ok(id) = id.parse() {
+            return Ok(Self::Id(id));
+        }
+        if let Some((Ok(first), Ok(last))) = s.split_once('-').map(|(a, b)| (a.parse(), b.parse())) {
+            if first > last {
+                return Err(format!("'{}' is an empty range; put the smaller ID first", s));
+            }
+            return Ok(Self::Range(first, last));
+        }
+        match s.trim() {
+            "" => Err("a task selector can't be empty".to_string()),
+            "last" => Ok(Self::Last),
+            name => Ok(Self::Name(name.to_string())),
+        }
+    }
+}
+
+impl From<TaskId> for TaskSelector {
+    fn from(id: TaskId) -> Self {
+        Self::Id(id)
+    }
+}
+
+impl fmt::Display for TaskSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Id(id) => write!(f, "#{}", id),
+            Self::Range(first, last) => write!(f, "{}-{}", first, last),
+            Self::Last => write!(f, "last"),
+            Self::Name(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+/// Every task `selector` could mean. A name that matches one task exactly wins over the
+/// tasks merely containing it, so `Login` still picks `Login` next to `Login page`.
+pub fn candidates<'a>(to_do_list: &'a ToDoList, selector: &TaskSelector) -> Vec<&'a Task> {
+    let tasks = to_do_list.tasks();
+    match selector {
+        TaskSelector::Id(id) => tasks.iter().filter(|t| t.id == *id).collect(),
+        TaskSelector::Range(first, last) => tasks.iter().filter(|t| (*first..=*last).contains(&t.id)).collect(),
+        // Tasks from before creation times were recorded count as older than any other.
+        TaskSelector::Last => tasks.iter().max_by_key(|t| (t.created, t.id)).into_iter().collect(),
+        TaskSelector::Name(name) => {
+            let name = name.to_lowercase();
+            let exact: Vec<&Task> = tasks.iter().filter(|t| t.name.to_lowercase() == name).collect();
+            if exact.len() == 1 {
+                return exact;
+            }
+            tasks.iter().filter(|t| t.name.to_lowercase().contains(&name)).collect()
+        }
+    }
+}
+
+/// The IDs every selector and the `filter` query pick out, in order and without repeats.
+/// Ranges may match several tasks; any other selector must match exactly one.
+pub fn resolve_all(to_do_list: &ToDoList, selectors: &[TaskSelector], filter: Option<&Query>) -> Result<Vec<TaskId>> {
+    let mut ids = Vec::new();
+    for selector in selectors {
+        match selector {
+            TaskSelector::Range(..) => {
+                let matched = candidates(to_do_list, selector);
+                if matched.is_empty() {
+                    return Err(Error::NoMatch(selector.to_string()));
+                }
+                ids.extend(matched.iter().map(|t| t.id));
+            }
+            _ => ids.push(resolve(to_do_list, selector)?),
+        }
+    }
+    if let Some(query) = filter {
+        let now = task::now();
+        ids.extend(to_do_list.tasks().iter().filter(|t| query.matches(t, now)).map(|t| t.id));
+    }
+    let mut seen = HashSet::new();
+    ids.retain(|id| seen.insert(*id));
+    Ok(ids)
+}
+
+/// The ID of the single task `selector` picks out.
+pub fn resolve(to_do_list: &ToDoList, selector: &TaskSelector) -> Result<TaskId> {
+    match (candidates(to_do_list, selector).as_slice(), selector) {
+        ([task], _) => Ok(task.id),
+        ([], TaskSelector::Id(id)) => Err(Error::NotFound(*id)),
+        ([], _) => Err(Error::NoMatch(selector.to_string())),
+        (tasks, _) => Err(Error::Ambiguous {
+            selector: selector.to_string(),
+            matches: tasks.iter().map(|t| (t.id, t.name.clone())).collect(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::Importance;
+
+    fn list() -> ToDoList {
+        let mut list = ToDoList::default();
+        list.add_task("Login".into(), Importance::Normal);
+        list.add_task("Login page".into(), Importance::Normal);
+        list.add_task("Fix the build".into(), Importance::Normal);
+        list
+    }
+
+    // Test 1: Selectors parse from IDs, #IDs, `last` and anything else as a name
+    #[test]
+    fn test_parse_selectors() {
+        assert_eq!("12".parse(), Ok(TaskSelector::Id(12)));
+        assert_eq!("#12".parse(), Ok(TaskSelector::Id(12)));
+        assert_eq!("last".parse(), Ok(TaskSelector::Last));
+        assert_eq!("build".parse(), Ok(TaskSelector::Name("build".into())));
+        assert_eq!("3-9".parse(), Ok(TaskSelector::Range(3, 9)));
+        assert_eq!("v1-rc".parse(), Ok(TaskSelector::Name("v1-rc".into())));
+        assert!("9-3".parse::<TaskSelector>().is_err());
+        assert!("".parse::<TaskSelector>().is_err());
+    }
+
+    // Test 2: Names resolve when they pick out one task, preferring an exact match
+    #[test]
+    fn test_resolve_names() {
+        // Arrange
+        let list = list();
+
+        // Act & Assert
+        assert_eq!(resolve(&list, &TaskSelector::Name("BUILD".into())).unwrap(), 3);
+        assert_eq!(resolve(&list, &TaskSelector::Name("login".into())).unwrap(), 1);
+        assert_eq!(resolve(&list, &TaskSelector::Last).unwrap(), 3);
+        assert!(matches!(resolve(&list, &TaskSelector::Name("deploy".into())), Err(Error::NoMatch(_))));
+        assert!(matches!(resolve(&list, &TaskSelector::Id(9)), Err(Error::NotFound(9))));
+    }
+
+    // Test 3: A name contained in several tasks is ambiguous
+    #[test]
+    fn test_resolve_ambiguous() {
+        // Arrange
+        let list = list();
+
+        // Act
+        let result = resolve(&list, &TaskSelector::Name("i".into()));
+
+        // Assert
+        match result {
+            Err(Error::Ambiguous { matches, .. }) => assert_eq!(matches.len(), 3),
+            other => panic!("expected an ambiguity error, got {:?}", other),
+        }
+    }
+
+    // Test 4: Ranges, lists and filters combine without repeats
+    #[test]
+    fn test_resolve_all() {
+        // Arrange
+        let mut list = list();
+        list.update_task(1, Some(crate::task::Status::Finished), None).unwrap();
+        let selectors = vec![TaskSelector::Id(3), TaskSelector::Range(1, 2)];
+        let filter: Query = "status:finished".parse().unwrap();
+
+        // Act
+        let ids = resolve_all(&list, &selectors, Some(&filter)).unwrap();
+        let empty = resolve_all(&list, &[TaskSelector::Range(7, 9)], None);
+
+        // Assert
+        assert_eq!(ids, vec![3, 1, 2]);
+        assert!(matches!(empty, Err(Error::NoMatch(_))));
+    }
+}
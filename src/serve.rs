@@ -0,0 +1,351 @@
+use std::fmt::Display;
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::json;
+use tiny_http::{Header, Request, Response, Server};
+
+use crate::cli::{AddArgs, RmArgs, ServeArgs, ShowArgs, UpdateArgs};
+use crate::commands;
+use crate::config::Config;
+use crate::error::{Error, Result};
+use crate::render::{self, Format, Render};
+use crate::select::TaskSelector;
+use crate::store::Store;
+use crate::task::ToDoList;
+
+/// Environment variable holding the token `rudden serve` requires, like `--token`.
+pub const TOKEN_ENV: &str = "RUDDEN_TOKEN";
+
+/// An HTTP status with the JSON body to send.
+#[derive(PartialEq, Eq, Debug)]
+pub struct Reply {
+    pub status: u16,
+    pub body: String,
+}
+
+impl Reply {
+    /// An outcome as `--format json` prints it.
+    fn outcome<T: Render>(status: u16, outcome: &T, config: &Config) -> Result<Self> {
+        Ok(Self { status, body: render::emit(outcome, Format::Json, false, config)? })
+    }
+
+    fn error(status: u16, message: impl Display) -> Self {
+        Self { status, body: json!({ "error": message.to_string() }).to_string() }
+    }
+}
+
+/// The body of `POST /tasks`.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NewTask {
+    name: String,
+    importance: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    parent: Option<u32>,
+    description: Option<String>,
+}
+
+/// The body of `PATCH /tasks/<task>`. Fields left out aren't changed.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TaskChanges {
+    status: Option<String>,
+    importance: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    untags: Vec<String>,
+    parent: Option<u32>,
+    #[serde(default)]
+    no_parent: bool,
+    description: Option<String>,
+}
+
+/// The HTTP status for a failed command: 404 when the task isn't there, 400 when the
+/// request asked for something invalid, and 500 when rudden itself failed.
+fn status_for(e: &Error) -> u16 {
+    match e.exit_code() {
+        3 => 404,
+        4 => 400,
+        _ => 500,
+    }
+}
+
+/// Decodes `%XX` escapes in a URL path segment or query value.
+fn decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| bytes.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// `GET /tasks` takes `show`'s query, sort keys, limit and offset as `q`, `sort`, `limit`
+/// and `offset`.
+fn show_args(query: &str) -> std::result::Result<ShowArgs, String> {
+    let mut args = ShowArgs::default();
+    for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let value = decode(&value.replace('+', " "));
+        match key {
+            "q" => args.query.push(value),
+            "sort" => args.sort = value.split(',').map(str::parse).collect::<std::result::Result<_, _>>()?,
+            "limit" => args.limit = Some(value.parse().map_err(|_| format!("'{}' is not a valid limit.", value))?),
+            "offset" => args.offset = value.parse().map_err(|_| format!("'{}' is not a valid offset.", value))?,
+            _ => return Err(format!("Unknown parameter '{}'. Use q, sort, limit or offset.", key)),
+        }
+    }
+    Ok(args)
+}
+
+/// Compares a sent token with the expected one without stopping at the first difference, so
+/// response times don't tell how much of a guess was right. Only the length can leak.
+fn same_token(sent: &[u8], expected: &[u8]) -> bool {
+    sent.len() == expected.len() && sent.iter().zip(expected).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// Whether a request's `Host` names this server: localhost, a loopback address, the host it
+/// was bound to, or any IP address when it listens on all of them. Another name means a web
+/// page may have pointed its own domain at this machine to reach the tasks (DNS rebinding).
+fn allowed_host(host: &str, bound: &str, addr: Option<SocketAddr>) -> bool {
+    let name = match host.strip_prefix('[') {
+        Some(rest) => rest.split(']').next().unwrap_or(rest),
+        None => host.rsplit_once(':').filter(|(_, port)| port.chars().all(|c| c.is_ascii_digit())).map_or(host, |(name, _)| name),
+    };
+    if name.eq_ignore_ascii_case("localhost") || name.eq_ignore_ascii_case(bound) {
+        return true;
+    }
+    match name.parse::<IpAddr>() {
+        Ok(ip) => ip.is_loopback() || addr.is_some_and(|addr| addr.ip() == ip || addr.ip().is_unspecified()),
+        Err(_) => false,
+    }
+}
+
+fn parse_body<T: DeserializeOwned>(body: &str) -> std::result::Result<T, String> {
+    serde_json::from_str(body).map_err(|e| format!("Invalid request body: {}", e))
+}
+
+/// Serves a store's tasks as JSON over HTTP. Each request takes the store's lock, reads the
+/// list afresh and saves what it changed, so the server and the command line can be used
+/// side by side.
+pub struct Api {
+    server: Server,
+    store: Store,
+    repo_path: PathBuf,
+    config: Config,
+    host: String,
+    token: Option<String>,
+}
+
+impl Api {
+    pub fn bind(args: &ServeArgs, store: Store, repo_path: PathBuf, config: Config) -> Result<Self> {
+        let server = Server::http((args.host.as_str(), args.port)).map_err(|e| Error::Storage(io::Error::other(e)))?;
+        Ok(Self { server, store, repo_path, config, host: args.host.clone(), token: args.token.clone() })
+    }
+
+    /// The address the server listens on, including the port picked for `--port 0`.
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.server.server_addr().to_ip()
+    }
+
+    /// Answers requests until the process is stopped.
+    pub fn run(&self) {
+        for mut request in self.server.incoming_requests() {
+            let reply = self.reply(&mut request);
+            let content_type = Header::from_bytes("Content-Type", "application/json").expect("the header is valid");
+            let response = Response::from_string(reply.body).with_status_code(reply.status).with_header(content_type);
+            // A client that hung up before reading its reply doesn't stop the server.
+            let _ = request.respond(response);
+        }
+    }
+
+    fn reply(&self, request: &mut Request) -> Reply {
+        let header = |name: &'static str| request.headers().iter().find(|header| header.field.equiv(name)).map(|header| header.value.as_str());
+        if header("Host").is_none_or(|host| !allowed_host(host, &self.host, self.local_addr())) {
+            return Reply::error(403, "The Host header doesn't name this server.");
+        }
+        if let Some(token) = &self.token {
+            let expected = format!("Bearer {}", token);
+            if header("Authorization").is_none_or(|authorization| !same_token(authorization.as_bytes(), expected.as_bytes())) {
+                return Reply::error(401, "Missing or wrong token. Send it as `Authorization: Bearer <token>`.");
+            }
+        }
+        // Browsers send other sites' forms without asking, but never with a JSON body.
+        let json = header("Content-Type").is_some_and(|value| value.split(';').next().unwrap_or("").trim().eq_ignore_ascii_case("application/json"));
+        let mut body = String::new();
+        if let Err(e) = request.as_reader().read_to_string(&mut body) {
+            return Reply::error(400, format!("Invalid request body: {}", e));
+        }
+        if !body.is_empty() && !json {
+            return Reply::error(415, "Request bodies must be sent as `Content-Type: application/json`.");
+        }
+        let (method, url) = (request.method().as_str().to_string(), request.url().to_string());
+        self.handle(&method, &url, &body).unwrap_or_else(|e| Reply::error(status_for(&e), e))
+    }
+
+    /// Runs the command a request maps to against the list as it is on disk now.
+    pub fn handle(&self, method: &str, url: &str, body: &str) -> Result<Reply> {
+        let (path, query) = url.split_once('?').unwrap_or((url, ""));
+        let segments: Vec<String> = path.split('/').filter(|segment| !segment.is_empty()).map(decode).collect();
+        let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+        let selector = |segment: &str| segment.parse::<TaskSelector>();
+        let config = &self.config;
+
+        let _lock = self.store.lock()?;
+        let mut to_do_list = ToDoList::load(self.store.tasks_path())?;
+        let reply = match (method, segments.as_slice()) {
+            ("GET", ["tasks"]) => {
+                return match show_args(query) {
+                    Ok(args) => Reply::outcome(200, &commands::show_tasks(&to_do_list, &args, config)?, config),
+                    Err(message) => Ok(Reply::error(400, message)),
+                };
+            }
+            ("POST", ["tasks"]) => {
+                let task: NewTask = match parse_body(body) {
+                    Ok(task) => task,
+                    Err(message) => return Ok(Reply::error(400, message)),
+                };
+                to_do_list.reserve_ids(&ToDoList::load(self.store.archive_path())?);
+                let args = AddArgs {
+                    message: task.name,
+                    importance: task.importance,
+                    tags: task.tags,
+                    parent: task.parent,
+                    description: task.description,
+                };
+                Reply::outcome(201, &commands::add_task(&mut to_do_list, &args, config)?, config)?
+            }
+            ("PATCH", ["tasks", task]) => {
+                let (task, changes) = match (selector(task), parse_body::<TaskChanges>(body)) {
+                    (Ok(task), Ok(changes)) => (task, changes),
+                    (Err(message), _) | (_, Err(message)) => return Ok(Reply::error(400, message)),
+                };
+                let args = UpdateArgs {
+                    tasks: vec![task],
+                    filter: None,
+                    status: changes.status,
+                    importance: changes.importance,
+                    tags: changes.tags,
+                    untags: changes.untags,
+                    parent: changes.parent,
+                    no_parent: changes.no_parent,
+                    description: changes.description,
+                };
+                Reply::outcome(200, &commands::update_task(&mut to_do_list, &args, config)?, config)?
+            }
+            ("DELETE", ["tasks", task]) => {
+                // Without `rm`'s confirmation, a request removes one task it names by ID.
+                let task = match selector(task) {
+                    Ok(task @ TaskSelector::Id(_)) => task,
+                    Ok(_) => return Ok(Reply::error(400, format!("DELETE takes a task ID, as in /tasks/12, not {}.", path))),
+                    Err(message) => return Ok(Reply::error(400, message)),
+                };
+                let args = RmArgs { tasks: vec![task], filter: None, yes: true };
+                Reply::outcome(200, &commands::remove_task(&mut to_do_list, &args, config)?, config)?
+            }
+            ("POST", ["check"]) => {
+                let mut archive = ToDoList::load(self.store.archive_path())?;
+                let mut last_checked = self.store.last_checked()?;
+                let outcome = commands::check_tasks(&mut to_do_list, &mut archive, &self.repo_path, config, &mut last_checked)?;
+                // Archive first and record the reflog entry last, as the command line does.
+                if config.archive_after().is_some() {
+                    archive.save(self.store.archive_path())?;
+                }
+                to_do_list.save(self.store.tasks_path())?;
+                if let Some(entry) = &last_checked {
+                    self.store.set_last_checked(entry)?;
+                }
+                return Reply::outcome(200, &outcome, config);
+            }
+            (_, ["tasks"] | ["tasks", _] | ["check"]) => return Ok(Reply::error(405, format!("{} isn't allowed on {}.", method, path))),
+            _ => return Ok(Reply::error(404, format!("No endpoint at {}.", path))),
+        };
+        to_do_list.save(self.store.tasks_path())?;
+        Ok(reply)
+    }
+}
+
+/// Serves the store until the process is stopped.
+pub fn run(args: &ServeArgs, store: Store, repo_path: PathBuf, config: Config) -> Result<()> {
+    let api = Api::bind(args, store, repo_path, config)?;
+    if let Some(addr) = api.local_addr() {
+        if !addr.ip().is_loopback() && api.token.is_none() {
+            eprintln!("Warning: anyone who can reach {} can read and change these tasks. Set --token to require a token.", addr);
+        }
+        eprintln!("Serving {} on http://{}", api.store.dir.display(), addr);
+    }
+    api.run();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sort::SortField;
+
+    // Test 1: Query strings are decoded into `show`'s arguments
+    #[test]
+    fn test_show_args_from_query() {
+        // Arrange
+        let query = "q=status%3Apending+tag:api&sort=-importance,id&limit=5";
+
+        // Act
+        let args = show_args(query).unwrap();
+        let unknown = show_args("colour=red");
+
+        // Assert
+        assert_eq!(args.query, vec!["status:pending tag:api"]);
+        assert_eq!(args.sort.iter().map(|key| (key.field, key.descending)).collect::<Vec<_>>(), vec![(SortField::Importance, true), (SortField::Id, false)]);
+        assert_eq!(args.limit, Some(5));
+        assert!(unknown.is_err());
+        assert_eq!(decode("Fix%20login%2"), "Fix login%2");
+    }
+
+    // Test 2: Tokens match only when every byte does
+    #[test]
+    fn test_same_token() {
+        // Act & Assert
+        assert!(same_token(b"Bearer s3cret", b"Bearer s3cret"));
+        assert!(!same_token(b"Bearer s3creT", b"Bearer s3cret"));
+        assert!(!same_token(b"Bearer s3cre", b"Bearer s3cret"));
+        assert!(!same_token(b"", b"Bearer s3cret"));
+    }
+
+    // Test 3: Only localhost, loopback addresses and the bound address are accepted as hosts
+    #[test]
+    fn test_allowed_host() {
+        // Arrange
+        let loopback: Option<SocketAddr> = "127.0.0.1:7420".parse().ok();
+        let everywhere: Option<SocketAddr> = "0.0.0.0:7420".parse().ok();
+
+        // Act & Assert
+        assert!(allowed_host("localhost:7420", "127.0.0.1", loopback));
+        assert!(allowed_host("127.0.0.1:7420", "127.0.0.1", loopback));
+        assert!(allowed_host("[::1]:7420", "127.0.0.1", loopback));
+        assert!(!allowed_host("evil.example:7420", "127.0.0.1", loopback));
+        assert!(!allowed_host("192.168.1.5:7420", "127.0.0.1", loopback));
+        assert!(allowed_host("192.168.1.5:7420", "0.0.0.0", everywhere));
+        assert!(allowed_host("tasks.lan", "tasks.lan", everywhere));
+        assert!(!allowed_host("evil.example", "0.0.0.0", everywhere));
+    }
+}
@@ -0,0 +1,257 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser};
+use rustyline::completion::{Completer, Pair};
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
+
+use crate::cli::Cli;
+use crate::error::{Error, Result};
+use crate::store;
+use crate::task::{TaskId, ToDoList};
+use crate::Session;
+
+/// Name of the shell's history file, inside rudden's data directory.
+pub const HISTORY_FILE: &str = "shell_history";
+
+const PROMPT: &str = "rudden> ";
+
+/// Subcommands whose positional arguments are task selectors.
+pub const TAKES_TASKS: [&str; 4] = ["update", "rm", "start", "commit-msg"];
+
+/// Options followed by a tag.
+const TAKES_TAG: [&str; 3] = ["-t", "--tag", "--untag"];
+
+/// Completes subcommands, their options, task IDs and tags from the list as it is now.
+#[derive(Default)]
+pub struct ShellHelper {
+    tasks: Vec<(TaskId, String)>,
+    tags: Vec<String>,
+}
+
+impl ShellHelper {
+    /// Picks up the tasks and tags the last command left.
+    pub fn refresh(&mut self, to_do_list: &ToDoList) {
+        self.tasks = to_do_list.tasks().iter().map(|t| (t.id, t.name.clone())).collect();
+        self.tags = to_do_list.tasks().iter().flat_map(|t| t.tags.iter().cloned()).collect();
+        self.tags.sort();
+        self.tags.dedup();
+    }
+
+    /// Where the word being completed starts in `line`, and what it could be.
+    pub fn candidates(&self, line: &str) -> (usize, Vec<Pair>) {
+        let start = line.rfind(char::is_whitespace).map_or(0, |i| i + 1);
+        let word = &line[start..];
+        let before: Vec<&str> = line[..start].split_whitespace().collect();
+        let pair = |display: String, replacement: String| Pair { display, replacement };
+        let matching = |options: Vec<(String, String)>| -> Vec<Pair> {
+            options.into_iter().filter(|(_, replacement)| replacement.starts_with(word)).map(|(d, r)| pair(d, r)).collect()
+        };
+
+        let command = Cli::command();
+        let Some(name) = before.first() else {
+            let mut names: Vec<String> = command.get_subcommands().filter(|c| !c.is_hide_set()).map(|c| c.get_name().to_string()).collect();
+            names.push("exit".to_string());
+            return (start, matching(names.into_iter().map(|n| (n.clone(), n)).collect()));
+        };
+        let Some(subcommand) = command.find_subcommand(name) else { return (start, Vec::new()) };
+
+        let options = if word.starts_with('-') {
+            let flags = subcommand.get_arguments().chain(command.get_arguments());
+            flags.filter_map(|arg| arg.get_long()).map(|long| format!("--{}", long)).map(|flag| (flag.clone(), flag)).collect()
+        } else if before.last().is_some_and(|previous| TAKES_TAG.contains(previous)) {
+            self.tags.iter().map(|tag| (tag.clone(), tag.clone())).collect()
+        } else if word.starts_with("tag:") {
+            self.tags.iter().map(|tag| (format!("tag:{}", tag), format!("tag:{}", tag))).collect()
+        } else if TAKES_TASKS.contains(name) {
+            self.tasks.iter().map(|(id, name)| (format!("{}  {}", id, name), id.to_string())).collect()
+        } else {
+            Vec::new()
+        };
+        (start, matching(options))
+    }
+}
+
+impl Completer for ShellHelper {
+    type Candidate = Pair;
+
+    fn complete(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> rustyline::Result<(usize, Vec<Pair>)> {
+        Ok(self.candidates(&line[..pos]))
+    }
+}
+
+impl Hinter for ShellHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ShellHelper {}
+
+impl Validator for ShellHelper {}
+
+impl Helper for ShellHelper {}
+
+fn readline_error(e: ReadlineError) -> Error {
+    Error::Storage(io::Error::other(e))
+}
+
+/// Whether `dir`, as given to `--store` from `current_dir`, is the store at `store_dir`.
+fn is_store(current_dir: &Path, dir: &Path, store_dir: &Path) -> bool {
+    match (current_dir.join(dir).canonicalize(), store_dir.canonicalize()) {
+        (Ok(dir), Ok(store_dir)) => dir == store_dir,
+        _ => false,
+    }
+}
+
+/// Runs one line as a rudden command. Returns what to print on stdout, or on stderr if the
+/// line couldn't be run.
+pub fn run_line(session: &mut Session, line: &str) -> std::result::Result<String, String> {
+    let words = shlex::split(line).ok_or_else(|| "Error: unmatched quote".to_string())?;
+    let cli = match Cli::try_parse_from(std::iter::once("rudden".to_string()).chain(words)) {
+        Ok(cli) => cli,
+        Err(e) if matches!(e.kind(), ErrorKind::DisplayHelp | ErrorKind::DisplayVersion) => return Ok(e.render().to_string()),
+        Err(e) => return Err(e.render().to_string()),
+    };
+    // They would hold the terminal, or the shell's line, until stopped.
+    if crate::is_long_running(&cli.mode) {
+        return Err("Error: shell, tui and serve can't run inside the shell.".to_string());
+    }
+    if cli.global != session.is_global() || cli.store.as_ref().is_some_and(|dir| !is_store(&session.current_dir, dir, &session.store().dir)) {
+        return Err("Error: the shell works on one store; start another shell for a different one.".to_string());
+    }
+    session.execute(cli).map_err(|e| format!("Error: {}", e))
+}
+
+/// Reads commands until `exit` or end of input, printing each one's output.
+pub fn run(session: &mut Session) -> Result<()> {
+    let mut editor: Editor<ShellHelper, DefaultHistory> = Editor::new().map_err(readline_error)?;
+    editor.set_helper(Some(ShellHelper::default()));
+    let history = store::data_dir().map(|dir| dir.join(HISTORY_FILE));
+    if let Some(history) = &history {
+        // There's no history the first time.
+        let _ = editor.load_history(history);
+    }
+
+    loop {
+        if let Some(helper) = editor.helper_mut() {
+            helper.refresh(session.to_do_list());
+        }
+        let line = match editor.readline(PROMPT) {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(e) => return Err(readline_error(e)),
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        editor.add_history_entry(line).map_err(readline_error)?;
+        if matches!(line, "exit" | "quit") {
+            break;
+        }
+        match run_line(session, line) {
+            Ok(output) if output.is_empty() => {}
+            Ok(output) => println!("{}", output.trim_end()),
+            Err(message) => eprintln!("{}", message.trim_end()),
+        }
+    }
+    let Some(history) = history else {
+        return Ok(());
+    };
+    if let Some(dir) = history.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    editor.save_history(&history).map_err(readline_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::Importance;
+
+    fn replacements(helper: &ShellHelper, line: &str) -> Vec<String> {
+        helper.candidates(line).1.into_iter().map(|pair| pair.replacement).collect()
+    }
+
+    // Test 1: Completion offers subcommands, options, task IDs and tags where each fits
+    #[test]
+    fn test_shell_completion() {
+        // Arrange
+        let mut list = ToDoList::default();
+        list.add_task("Fix login".to_string(), Importance::Normal);
+        list.tag_task(1, &["auth".to_string()], &[]).unwrap();
+        let mut helper = ShellHelper::default();
+        helper.refresh(&list);
+
+        // Act & Assert
+        assert_eq!(replacements(&helper, "se"), vec!["search", "serve"]);
+        assert_eq!(replacements(&helper, "update "), vec!["1"]);
+        assert_eq!(helper.candidates("rm ").1[0].display, "1  Fix login");
+        assert_eq!(replacements(&helper, "add -m x -t a"), vec!["auth"]);
+        assert_eq!(replacements(&helper, "show tag:"), vec!["tag:auth"]);
+        assert!(replacements(&helper, "show --gr").contains(&"--group-by".to_string()));
+        assert_eq!(helper.candidates("update 1 --st").0, 9);
+    }
+
+    // Test 2: Lines run against the shell's store, however it is named, and save after each change
+    #[test]
+    fn test_shell_runs_lines() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let store = dir.path().join(".rudden");
+        std::fs::create_dir(&store).unwrap();
+        let cli = Cli::parse_from(["rudden", "--store", store.to_str().unwrap(), "shell"]);
+        let mut session = Session::open(&cli).unwrap();
+
+        // Act
+        let added = run_line(&mut session, "add -m 'Write docs'");
+        let unknown = run_line(&mut session, "frobnicate");
+        let nested = run_line(&mut session, "shell");
+        let tui = run_line(&mut session, "tui");
+        let serve = run_line(&mut session, "serve --port 0");
+        let shown = run_line(&mut session, "show -q");
+        let same_store = run_line(&mut session, &format!("--store {}/../.rudden show -q", store.display()));
+        let other_store = run_line(&mut session, &format!("--store {} show -q", dir.path().display()));
+
+        // Assert
+        assert_eq!(added, Ok("Successfully added task with ID: 1".to_string()));
+        assert!(unknown.unwrap_err().contains("unrecognized subcommand"));
+        assert!(nested.unwrap_err().contains("can't run inside the shell"));
+        assert!(tui.unwrap_err().contains("can't run inside the shell"));
+        assert!(serve.unwrap_err().contains("can't run inside the shell"));
+        assert_eq!(shown, Ok("1".to_string()));
+        assert_eq!(same_store, Ok("1".to_string()));
+        assert!(other_store.unwrap_err().contains("works on one store"));
+        assert_eq!(ToDoList::load(store.join(crate::store::TASKS_FILE)).unwrap().tasks()[0].name, "Write docs");
+    }
+
+    // Test 3: Each line reads the list afresh, so changes made elsewhere meanwhile are kept
+    #[test]
+    fn test_shell_reloads_each_line() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let store = dir.path().join(".rudden");
+        std::fs::create_dir(&store).unwrap();
+        let cli = Cli::parse_from(["rudden", "--store", store.to_str().unwrap(), "shell"]);
+        let mut session = Session::open(&cli).unwrap();
+        let path = store.join(crate::store::TASKS_FILE);
+
+        // Act
+        run_line(&mut session, "add -m 'From the shell'").unwrap();
+        let mut elsewhere = ToDoList::load(&path).unwrap();
+        elsewhere.add_task("From elsewhere".to_string(), Importance::Normal);
+        elsewhere.save(&path).unwrap();
+        let added = run_line(&mut session, "add -m 'From the shell again'");
+
+        // Assert
+        assert_eq!(added, Ok("Successfully added task with ID: 3".to_string()));
+        assert_eq!(ToDoList::load(&path).unwrap().tasks().len(), 3);
+    }
+}
@@ -0,0 +1,217 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::outcome::{ListedTask, Origin, TaskGroup};
+use crate::task::{Importance, Task, TaskId};
+
+/// A field `show --sort` can order by.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum SortField {
+    Id,
+    Name,
+    Status,
+    Importance,
+    Created,
+}
+
+/// One `--sort` key. Fields sort in their natural order (pending before finished, normal
+/// before urgent, oldest first); a leading `-` reverses that.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct SortKey {
+    pub field: SortField,
+    pub descending: bool,
+}
+
+impl FromStr for SortKey {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (descending, name) = match s.strip_prefix('-') {
+            Some(name) => (true, name),
+            None => (false, s),
+        };
+        let field = match name {
+            "id" => SortField::Id,
+            "name" => SortField::Name,
+            "status" => SortField::Status,
+            "importance" => SortField::Importance,
+            "created" => SortField::Created,
+            _ => {
+                return Err(format!(
+                    "'{}' is not a valid sort key. Use 'id', 'name', 'status', 'importance' or 'created', optionally prefixed with '-'.",
+                    s
+                ))
+            }
+        };
+        Ok(Self { field, descending })
+    }
+}
+
+impl SortKey {
+    fn compare(&self, a: &Task, b: &Task) -> Ordering {
+        let ordering = match self.field {
+            SortField::Id => a.id.cmp(&b.id),
+            SortField::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+            SortField::Status => a.status.cmp(&b.status),
+            SortField::Importance => a.importance.cmp(&b.importance),
+            // Tasks without a recorded creation time count as the oldest.
+            SortField::Created => a.created.cmp(&b.created),
+        };
+        if self.descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    }
+}
+
+/// Sorts by each key in turn. The sort is stable, so ties keep their stored order.
+pub fn sort_tasks(tasks: &mut [ListedTask], keys: &[SortKey]) {
+    tasks.sort_by(|a, b| {
+        keys.iter().map(|key| key.compare(&a.task, &b.task)).find(|o| o.is_ne()).unwrap_or(Ordering::Equal)
+    });
+}
+
+/// What `show --group-by` splits tasks by.
+#[derive(ValueEnum, PartialEq, Eq, Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GroupBy {
+    /// Pending, started, then finished.
+    Status,
+    /// Most important first.
+    Importance,
+    /// A task with several tags appears under each of them.
+    Tag,
+    /// Subtasks under their parent, top-level tasks last.
+    Parent,
+}
+
+/// Splits tasks into groups, keeping their order within each group. Statuses come in
+/// workflow order, importances most important first, tags alphabetically and parents by ID,
+/// with untagged and top-level tasks last. `parent_name` looks up a parent's name for its header.
+pub fn group_tasks<F>(tasks: &[ListedTask], group_by: GroupBy, parent_name: F) -> Vec<TaskGroup>
+where
+    F: Fn(Option<Origin>, TaskId) -> Option<String>,
+{
+    // Keyed by (rank, name) so the map iterates in display order.
+    let mut groups: BTreeMap<(u64, String), Vec<ListedTask>> = BTreeMap::new();
+    for listed in tasks {
+        let task = &listed.task;
+        let keys = match group_by {
+            GroupBy::Status => vec![(task.status.clone() as u64, task.status.to_string())],
+            GroupBy::Importance => {
+                vec![(Importance::Urgent as u64 - task.importance.clone() as u64, task.importance.to_string())]
+            }
+            GroupBy::Tag if task.tags.is_empty() => vec![(1, "untagged".to_string())],
+            GroupBy::Tag => task.tags.iter().map(|tag| (0, tag.clone())).collect(),
+            GroupBy::Parent => match task.parent {
+                Some(parent) => {
+                    let name = match parent_name(listed.origin, parent) {
+                        Some(name) => format!("#{} {}", parent, name),
+                        None => format!("#{}", parent),
+                    };
+                    let name = match listed.origin {
+                        Some(origin) => format!("[{}] {}", origin, name),
+                        None => name,
+                    };
+                    vec![(u64::from(parent), name)]
+                }
+                None => vec![(u64::MAX, "no parent".to_string())],
+            },
+        };
+        for key in keys {
+            groups.entry(key).or_default().push(listed.clone());
+        }
+    }
+    groups.into_iter().map(|((_, name), tasks)| TaskGroup { name, tasks }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::Status;
+
+    fn listed(id: u32, name: &str, importance: Importance, created: Option<u64>) -> ListedTask {
+        let task = Task { id, name: name.to_string(), importance, created, ..Default::default() };
+        ListedTask { origin: None, task }
+    }
+
+    fn ids(tasks: &[ListedTask]) -> Vec<TaskId> {
+        tasks.iter().map(|listed| listed.task.id).collect()
+    }
+
+    // Test 1: Later keys break ties left by earlier ones, and '-' reverses a key
+    #[test]
+    fn test_sort_tasks_multi_key() {
+        // Arrange
+        let mut tasks = vec![
+            listed(1, "a", Importance::Normal, Some(10)),
+            listed(2, "b", Importance::Urgent, Some(10)),
+            listed(3, "c", Importance::Urgent, Some(30)),
+            listed(4, "d", Importance::Normal, None),
+        ];
+        let keys: Vec<SortKey> = ["-importance", "-created", "id"].iter().map(|k| k.parse().unwrap()).collect();
+
+        // Act
+        sort_tasks(&mut tasks, &keys);
+
+        // Assert
+        assert_eq!(ids(&tasks), vec![3, 2, 1, 4]);
+    }
+
+    // Test 2: Unknown sort keys are rejected
+    #[test]
+    fn test_sort_key_invalid() {
+        assert!("-due".parse::<SortKey>().is_err());
+        assert_eq!(
+            "-name".parse::<SortKey>(),
+            Ok(SortKey { field: SortField::Name, descending: true })
+        );
+    }
+
+    // Test 3: Groups come in a fixed order, and tasks keep their order within a group
+    #[test]
+    fn test_group_tasks_by_status_and_tag() {
+        // Arrange
+        let mut tasks = vec![
+            listed(1, "a", Importance::Normal, None),
+            listed(2, "b", Importance::Normal, None),
+            listed(3, "c", Importance::Normal, None),
+        ];
+        tasks[0].task.status = Status::Finished;
+        tasks[1].task.tags = vec!["ui".into(), "api".into()];
+        tasks[2].task.tags = vec!["api".into()];
+
+        // Act
+        let by_status = group_tasks(&tasks, GroupBy::Status, |_, _| None);
+        let by_tag = group_tasks(&tasks, GroupBy::Tag, |_, _| None);
+
+        // Assert
+        let summary = |groups: &[TaskGroup]| -> Vec<(String, Vec<TaskId>)> {
+            groups.iter().map(|group| (group.name.clone(), ids(&group.tasks))).collect()
+        };
+        assert_eq!(summary(&by_status), vec![("pending".into(), vec![2, 3]), ("finished".into(), vec![1])]);
+        assert_eq!(
+            summary(&by_tag),
+            vec![("api".into(), vec![2, 3]), ("ui".into(), vec![2]), ("untagged".into(), vec![1])]
+        );
+    }
+
+    // Test 4: Parent groups are headed by the parent's ID and name
+    #[test]
+    fn test_group_tasks_by_parent() {
+        // Arrange
+        let mut tasks = vec![listed(2, "Changelog", Importance::Normal, None), listed(3, "Other", Importance::Normal, None)];
+        tasks[0].task.parent = Some(1);
+
+        // Act
+        let groups = group_tasks(&tasks, GroupBy::Parent, |_, id| (id == 1).then(|| "Release".to_string()));
+
+        // Assert
+        let names: Vec<&str> = groups.iter().map(|group| group.name.as_str()).collect();
+        assert_eq!(names, vec!["#1 Release", "no parent"]);
+    }
+}
@@ -0,0 +1,174 @@
+use std::env;
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::git;
+
+/// Name of the store directory created by `rudden init`.
+pub const STORE_DIR: &str = ".rudden";
+/// Name of the task file inside the store directory.
+pub const TASKS_FILE: &str = ".rudden";
+/// Name of the file finished tasks are archived to, inside the store directory.
+pub const ARCHIVE_FILE: &str = "archive";
+/// Name of the file recording the last reflog entry `check` processed, inside the store directory.
+pub const CHECKED_FILE: &str = "checked";
+/// Name of the file rudden locks while it reads and writes the store, inside the store directory.
+pub const LOCK_FILE: &str = "lock";
+/// Environment variable that overrides store discovery, like `--store`.
+pub const STORE_ENV: &str = "RUDDEN_DIR";
+
+/// Where a rudden task list lives on disk.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Store {
+    pub dir: PathBuf,
+}
+
+impl Store {
+    /// Finds the store for `start`: an explicit override wins, then the nearest ancestor
+    /// holding a `.rudden` directory, then the root of the enclosing git repository.
+    /// Outside any repository, the store would live in `start` itself.
+    pub fn locate(start: &Path, override_dir: Option<&Path>) -> Self {
+        if let Some(dir) = override_dir {
+            return Self { dir: start.join(dir) };
+        }
+
+        for ancestor in start.ancestors() {
+            let candidate = ancestor.join(STORE_DIR);
+            if candidate.is_dir() {
+                return Self { dir: candidate };
+            }
+            if git::is_repo_root(ancestor) {
+                return Self { dir: candidate };
+            }
+        }
+        Self { dir: start.join(STORE_DIR) }
+    }
+
+    /// The user-level store for personal tasks, under the XDG data directory.
+    pub fn global() -> io::Result<Self> {
+        match data_dir() {
+            Some(dir) => Ok(Self { dir: dir.join("global") }),
+            None => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "can't locate the personal store: neither XDG_DATA_HOME nor HOME is set",
+            )),
+        }
+    }
+
+    pub fn exists(&self) -> bool {
+        self.dir.is_dir()
+    }
+
+    pub fn tasks_path(&self) -> PathBuf {
+        self.dir.join(TASKS_FILE)
+    }
+
+    /// Archived tasks, kept out of the task file so it stays small.
+    pub fn archive_path(&self) -> PathBuf {
+        self.dir.join(ARCHIVE_FILE)
+    }
+
+    /// The last reflog entry `check` processed, if it has run before.
+    pub fn last_checked(&self) -> io::Result<Option<String>> {
+        match fs::read_to_string(self.dir.join(CHECKED_FILE)) {
+            Ok(entry) => Ok(Some(entry.trim_end().to_string()).filter(|entry| !entry.is_empty())),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn set_last_checked(&self, entry: &str) -> io::Result<()> {
+        fs::write(self.dir.join(CHECKED_FILE), format!("{}\n", entry))
+    }
+
+    /// Waits for, then takes, the store's exclusive lock, so a command's read and write of
+    /// the task list don't interleave with another rudden process's. Dropping the file
+    /// releases it.
+    pub fn lock(&self) -> io::Result<File> {
+        let file = OpenOptions::new().write(true).create(true).truncate(false).open(self.dir.join(LOCK_FILE))?;
+        file.lock()?;
+        Ok(file)
+    }
+}
+
+/// rudden's per-user data directory: `$XDG_DATA_HOME/rudden`, or `~/.local/share/rudden`.
+pub fn data_dir() -> Option<PathBuf> {
+    resolve_data_dir(env::var_os("XDG_DATA_HOME"), env::var_os("HOME"))
+}
+
+fn resolve_data_dir(xdg_data_home: Option<OsString>, home: Option<OsString>) -> Option<PathBuf> {
+    // The XDG spec says relative values must be ignored.
+    let base = match xdg_data_home.map(PathBuf::from) {
+        Some(xdg) if xdg.is_absolute() => xdg,
+        _ => PathBuf::from(home?).join(".local").join("share"),
+    };
+    Some(base.join("rudden"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_locate_walks_up_to_existing_store() {
+        // Arrange
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join(STORE_DIR)).unwrap();
+        let nested = dir.path().join("src").join("deep");
+        fs::create_dir_all(&nested).unwrap();
+
+        // Act
+        let store = Store::locate(&nested, None);
+
+        // Assert
+        assert_eq!(store.dir, dir.path().join(STORE_DIR));
+        assert!(store.exists());
+    }
+
+    #[test]
+    fn test_locate_stops_at_git_root() {
+        // Arrange
+        let dir = tempdir().unwrap();
+        let repo = dir.path().join("repo");
+        fs::create_dir_all(repo.join(".git")).unwrap();
+        let nested = repo.join("src");
+        fs::create_dir_all(&nested).unwrap();
+
+        // Act
+        let store = Store::locate(&nested, None);
+
+        // Assert
+        assert_eq!(store.dir, repo.join(STORE_DIR));
+        assert!(!store.exists());
+    }
+
+    #[test]
+    fn test_locate_prefers_override() {
+        // Arrange
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join(STORE_DIR)).unwrap();
+
+        // Act
+        let store = Store::locate(dir.path(), Some(Path::new("elsewhere")));
+
+        // Assert
+        assert_eq!(store.dir, dir.path().join("elsewhere"));
+    }
+
+    #[test]
+    fn test_resolve_data_dir() {
+        assert_eq!(
+            resolve_data_dir(Some("/xdg".into()), Some("/home/u".into())),
+            Some(PathBuf::from("/xdg/rudden"))
+        );
+        assert_eq!(
+            resolve_data_dir(Some("relative".into()), Some("/home/u".into())),
+            Some(PathBuf::from("/home/u/.local/share/rudden"))
+        );
+        assert_eq!(resolve_data_dir(None, None), None);
+    }
+}
@@ -0,0 +1,300 @@
+use std::fmt;
+use std::str::FromStr;
+
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::config::Config;
+use crate::outcome::ListedTask;
+use crate::task::{self, Importance, Status, Task};
+
+/// A column of the `show` table.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Column {
+    Id,
+    Name,
+    Status,
+    Importance,
+    Age,
+    Tags,
+}
+
+impl FromStr for Column {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "id" => Ok(Self::Id),
+            "name" => Ok(Self::Name),
+            "status" => Ok(Self::Status),
+            "importance" => Ok(Self::Importance),
+            "age" => Ok(Self::Age),
+            "tags" => Ok(Self::Tags),
+            _ => Err(format!(
+                "'{}' is not a valid column. Use 'id', 'name', 'status', 'importance', 'age' or 'tags'.",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Column {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let column = match self {
+            Self::Id => "id",
+            Self::Name => "name",
+            Self::Status => "status",
+            Self::Importance => "importance",
+            Self::Age => "age",
+            Self::Tags => "tags",
+        };
+        write!(f, "{}", column)
+    }
+}
+
+/// When to color output. `Auto` is resolved against the terminal and `NO_COLOR` at startup.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+impl FromStr for ColorChoice {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Self::Auto),
+            "always" => Ok(Self::Always),
+            "never" => Ok(Self::Never),
+            _ => Err(format!("'{}' is not a valid color choice. Use 'auto', 'always' or 'never'.", s)),
+        }
+    }
+}
+
+/// Names are never truncated below this many characters, even on narrow terminals.
+const MIN_NAME_WIDTH: usize = 10;
+const SEPARATOR: &str = "  ";
+
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+fn importance_color(importance: &Importance) -> Option<&'static str> {
+    match importance {
+        Importance::Urgent => Some("\x1b[1;31m"),
+        Importance::Important => Some("\x1b[33m"),
+        Importance::Normal => None,
+    }
+}
+
+fn status_color(status: &Status) -> Option<&'static str> {
+    match status {
+        Status::Started => Some("\x1b[36m"),
+        Status::Finished => Some("\x1b[32m"),
+        Status::Pending => None,
+    }
+}
+
+/// How long ago a task was added, in the largest whole unit.
+pub fn format_age(seconds: u64) -> String {
+    match seconds {
+        s if s < 3600 => format!("{}m", s / 60),
+        s if s < 86_400 => format!("{}h", s / 3600),
+        s => format!("{}d", s / 86_400),
+    }
+}
+
+fn cell(task: &Task, column: Column, now: u64) -> String {
+    match column {
+        Column::Id => task.id.to_string(),
+        Column::Name => task.name.clone(),
+        Column::Status => task.status.to_string(),
+        Column::Importance => task.importance.to_string(),
+        Column::Age => task.created.map_or_else(|| "-".to_string(), |created| format_age(now.saturating_sub(created))),
+        Column::Tags => task.tags.join(","),
+    }
+}
+
+/// Shortens `text` to at most `width` terminal columns, ending it with `…`. Wide characters,
+/// such as CJK ones, take two columns.
+fn truncate(text: &str, width: usize) -> String {
+    if text.width() <= width {
+        return text.to_string();
+    }
+    let mut truncated = String::new();
+    let mut used = 0;
+    for c in text.chars() {
+        used += c.width().unwrap_or(0);
+        if used > width.saturating_sub(1) {
+            break;
+        }
+        truncated.push(c);
+    }
+    truncated.push('…');
+    truncated
+}
+
+/// Renders tasks as an aligned table with the configured columns, colors and width.
+/// An `origin` column is added when tasks come from several lists.
+pub fn render_table(tasks: &[ListedTask], config: &Config) -> String {
+    let columns = config.show_columns();
+    let color = config.color() == ColorChoice::Always;
+    let now = task::now();
+    let with_origin = tasks.iter().any(|listed| listed.origin.is_some());
+
+    let mut headers: Vec<String> = columns.iter().map(|c| c.to_string().to_uppercase()).collect();
+    let mut rows: Vec<Vec<String>> = tasks
+        .iter()
+        .map(|listed| columns.iter().map(|column| cell(&listed.task, *column, now)).collect())
+        .collect();
+    if with_origin {
+        headers.insert(0, "ORIGIN".to_string());
+        for (row, listed) in rows.iter_mut().zip(tasks) {
+            row.insert(0, listed.origin.map(|o| o.to_string()).unwrap_or_default());
+        }
+    }
+    let offset = usize::from(with_origin);
+
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.width()).collect();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.width());
+        }
+    }
+
+    // Only names are shortened to fit; every other column keeps its full width.
+    if let (Some(limit), Some(name)) = (config.show_width(), columns.iter().position(|c| *c == Column::Name)) {
+        let name = name + offset;
+        let others: usize = widths.iter().enumerate().filter(|(i, _)| *i != name).map(|(_, w)| w).sum();
+        let available = limit.saturating_sub(others + SEPARATOR.len() * (widths.len() - 1)).max(MIN_NAME_WIDTH);
+        if widths[name] > available {
+            widths[name] = available;
+            for row in &mut rows {
+                row[name] = truncate(&row[name], available);
+            }
+        }
+    }
+
+    let format_row = |cells: &[String], colors: &[Option<&str>]| {
+        let last = cells.len() - 1;
+        let parts: Vec<String> = cells
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| {
+                // Pad before coloring, since escape codes have no width; the last column isn't padded.
+                // A name cut before a wide character can be a column short.
+                let padding = if i == last { 0 } else { widths[i].saturating_sub(cell.width()) };
+                match colors.get(i).copied().flatten().filter(|_| color) {
+                    Some(code) => format!("{}{}{}{}", code, cell, RESET, " ".repeat(padding)),
+                    None => format!("{}{}", cell, " ".repeat(padding)),
+                }
+            })
+            .collect();
+        parts.join(SEPARATOR)
+    };
+
+    let mut lines = vec![format_row(&headers, &vec![Some(BOLD); headers.len()])];
+    for (row, listed) in rows.iter().zip(tasks) {
+        let mut colors = vec![None; offset];
+        colors.extend(columns.iter().map(|column| match column {
+            Column::Status => status_color(&listed.task.status),
+            Column::Importance => importance_color(&listed.task.importance),
+            _ => None,
+        }));
+        lines.push(format_row(row, &colors));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn listed(id: u32, name: &str, importance: Importance) -> ListedTask {
+        let task = Task { id, name: name.to_string(), importance, tags: vec!["api".into()], ..Default::default() };
+        ListedTask { origin: None, task }
+    }
+
+    // Test 1: Columns are aligned and the last one isn't padded
+    #[test]
+    fn test_render_table_aligns_columns() {
+        // Arrange
+        let mut config = Config::default();
+        config.set_cli("show.columns", "id,importance,tags,name").unwrap();
+        let tasks = vec![listed(1, "Short", Importance::Urgent), listed(12, "Longer name", Importance::Normal)];
+
+        // Act
+        let output = render_table(&tasks, &config);
+
+        // Assert
+        assert_eq!(
+            output,
+            "ID  IMPORTANCE  TAGS  NAME\n\
+             1   urgent      api   Short\n\
+             12  normal      api   Longer name"
+        );
+    }
+
+    // Test 2: Long names are truncated to the configured width
+    #[test]
+    fn test_render_table_truncates_names() {
+        // Arrange
+        let mut config = Config::default();
+        config.set_cli("show.columns", "id,name").unwrap();
+        config.set_cli("show.width", "16").unwrap();
+        let tasks = vec![listed(1, "A rather long task name", Importance::Normal)];
+
+        // Act
+        let output = render_table(&tasks, &config);
+
+        // Assert
+        assert_eq!(output.lines().nth(1), Some("1   A rather lo…"));
+    }
+
+    // Test 3: Colors are only written when enabled, and wrap the cell without its padding
+    #[test]
+    fn test_render_table_colors() {
+        // Arrange
+        let mut config = Config::default();
+        config.set_cli("show.columns", "importance,id").unwrap();
+        config.set_cli("show.color", "always").unwrap();
+        let tasks = vec![listed(1, "Fire", Importance::Urgent)];
+
+        // Act
+        let output = render_table(&tasks, &config);
+
+        // Assert
+        assert_eq!(output.lines().nth(1), Some("\x1b[1;31murgent\x1b[0m      1"));
+    }
+
+    // Test 4: Ages use the largest whole unit
+    #[test]
+    fn test_format_age() {
+        assert_eq!(format_age(59), "0m");
+        assert_eq!(format_age(2 * 3600 + 5), "2h");
+        assert_eq!(format_age(3 * 86_400), "3d");
+    }
+
+    // Test 5: Wide characters count as two columns when aligning and truncating
+    #[test]
+    fn test_render_table_wide_characters() {
+        // Arrange
+        let mut config = Config::default();
+        config.set_cli("show.columns", "name,id").unwrap();
+        let tasks = vec![listed(1, "修复登录", Importance::Normal), listed(2, "Fix login", Importance::Normal)];
+        let mut narrow = Config::default();
+        narrow.set_cli("show.columns", "id,name").unwrap();
+        narrow.set_cli("show.width", "14").unwrap();
+
+        // Act
+        let output = render_table(&tasks, &config);
+        let truncated = render_table(&[listed(1, "修复登录页面和注册页", Importance::Normal)], &narrow);
+
+        // Assert
+        assert_eq!(
+            output,
+            "NAME       ID\n\
+             修复登录   1\n\
+             Fix login  2"
+        );
+        assert_eq!(truncated.lines().nth(1), Some("1   修复登录…"));
+    }
+}
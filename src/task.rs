@@ -1,21 +1,32 @@
 use std::fmt;
-use std::fs::{File, OpenOptions};
-use std::io::{self, BufRead, BufReader, Write};
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::Path;
 use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
 
-#[derive(PartialEq, Eq, Debug, Clone)]
+use serde::Serialize;
+
+use crate::error::{Error, Result};
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Status {
+    #[default]
     Pending,
+    /// In progress, e.g. after `rudden start`.
+    Started,
     Finished,
 }
 
 impl FromStr for Status {
-    type Err = ();
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
         match s {
             "pending" => Ok(Self::Pending),
+            "started" => Ok(Self::Started),
             "finished" => Ok(Self::Finished),
-            _ => Err(()),
+            _ => Err(Error::InvalidStatus(s.to_string())),
         }
     }
 }
@@ -24,27 +35,30 @@ impl fmt::Display for Status {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let status = match self {
             Self::Pending => "pending",
+            Self::Started => "started",
             Self::Finished => "finished",
         };
         write!(f, "{}", status)
     }
 }
 
-#[derive(PartialEq, Eq, Debug, Clone)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Importance {
+    #[default]
     Normal,
     Important,
     Urgent,
 }
 
 impl FromStr for Importance {
-    type Err = ();
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
         match s {
             "normal" => Ok(Self::Normal),
             "important" => Ok(Self::Important),
             "urgent" => Ok(Self::Urgent),
-            _ => Err(()),
+            _ => Err(Error::InvalidImportance(s.to_string())),
         }
     }
 }
@@ -60,31 +74,212 @@ impl fmt::Display for Importance {
     }
 }
 
+pub type TaskId = u32;
+
+/// A line in the repository, written `path:line` with the path relative to the root.
 #[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Location {
+    pub path: String,
+    pub line: usize,
+}
+
+impl FromStr for Location {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        let (path, line) = s.rsplit_once(':').ok_or_else(|| parse_error("Invalid location"))?;
+        let line = line.parse().map_err(|_| parse_error("Invalid location line"))?;
+        Ok(Self { path: path.to_string(), line })
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.path, self.line)
+    }
+}
+
+impl Serialize for Location {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize)]
 pub struct Task {
-    pub id: u32,
+    pub id: TaskId,
     pub name: String,
     pub status: Status,
     pub importance: Importance,
+    /// When the task was added, in seconds since the Unix epoch.
+    /// Unknown for tasks saved before rudden recorded it.
+    pub created: Option<u64>,
+    pub tags: Vec<String>,
+    /// The task this one is a subtask of.
+    pub parent: Option<TaskId>,
+    /// Longer notes than fit in the name.
+    pub description: Option<String>,
+    /// When the task was last finished, in seconds since the Unix epoch.
+    /// Unknown for tasks finished before rudden recorded it.
+    pub finished: Option<u64>,
+    /// The `TODO` comment the task was found in by `rudden scan`.
+    pub location: Option<Location>,
+    /// Metadata fields this version doesn't know, or couldn't read, as they were written, so
+    /// saving doesn't lose them.
+    #[serde(skip)]
+    pub unknown: Vec<(String, String)>,
 }
 
 impl Task {
+    /// Metadata follows the importance as `key=value` fields. Files written before there was
+    /// any still load, but not the other way round: rudden versions from before metadata
+    /// can't parse a line that has it, and drop that task the next time they save.
     fn to_csv_line(&self) -> String {
-        format!(
+        let mut line = format!(
             "{},{},{},{}",
-            self.id, self.name, self.status, self.importance
-        )
+            self.id, escape_name(&self.name), self.status, self.importance
+        );
+        if let Some(created) = self.created {
+            line.push_str(&format!(",created={}", created));
+        }
+        if !self.tags.is_empty() {
+            line.push_str(&format!(",tags={}", escape(&self.tags.join(";"))));
+        }
+        if let Some(parent) = self.parent {
+            line.push_str(&format!(",parent={}", parent));
+        }
+        if let Some(description) = &self.description {
+            line.push_str(&format!(",description={}", escape(description)));
+        }
+        if let Some(finished) = self.finished {
+            line.push_str(&format!(",finished={}", finished));
+        }
+        if let Some(location) = &self.location {
+            line.push_str(&format!(",location={}", escape(&location.to_string())));
+        }
+        for (key, value) in &self.unknown {
+            line.push_str(&format!(",{}={}", key, value));
+        }
+        line
+    }
+
+    /// Changes the status, recording when the task was finished.
+    fn set_status(&mut self, status: Status) {
+        if status == Status::Finished && self.status != Status::Finished {
+            self.finished = Some(now());
+        } else if status != Status::Finished {
+            self.finished = None;
+        }
+        self.status = status;
+    }
+
+    /// Seconds since the task was finished, falling back to its age for tasks finished before
+    /// that was recorded. `None` if the task isn't finished or neither time is known.
+    pub fn finished_ago(&self, now: u64) -> Option<u64> {
+        if self.status != Status::Finished {
+            return None;
+        }
+        self.finished.or(self.created).map(|time| now.saturating_sub(time))
+    }
+
+    /// Sets a metadata field. Fails for keys this version doesn't know, as for invalid values.
+    fn set_metadata(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "created" => self.created = Some(value.parse().map_err(|_| parse_error("Invalid created time"))?),
+            "tags" => self.tags = value.split(';').filter(|t| !t.is_empty()).map(str::to_string).collect(),
+            "parent" => self.parent = Some(value.parse().map_err(|_| parse_error("Invalid parent ID"))?),
+            "description" => self.description = Some(value.to_string()),
+            "finished" => self.finished = Some(value.parse().map_err(|_| parse_error("Invalid finished time"))?),
+            "location" => self.location = Some(value.parse()?),
+            _ => return Err(parse_error("Unknown field")),
+        }
+        Ok(())
+    }
+}
+
+/// Seconds since the Unix epoch, as stored in `Task::created`.
+pub fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// Tags are stored `;`-separated inside a comma-separated line, and queried as `tag:<name>`.
+pub fn validate_tag(tag: &str) -> Result<()> {
+    if tag.is_empty() || tag.contains(|c: char| c.is_whitespace() || c == ',' || c == ';') {
+        return Err(Error::InvalidTag(tag.to_string()));
+    }
+    Ok(())
+}
+
+/// Splits a trailing `key=value` metadata field, if `field` is one.
+fn metadata_field(field: &str) -> Option<(&str, &str)> {
+    let (key, value) = field.split_once('=')?;
+    let is_key = !key.is_empty() && key.chars().all(|c| c.is_ascii_lowercase() || c == '_');
+    is_key.then_some((key, value))
+}
+
+/// Percent-escapes the characters that would break a metadata field.
+fn escape(value: &str) -> String {
+    escape_name(value).replace(',', "%2C")
+}
+
+/// Percent-escapes the characters that would break a task's line. Names keep their commas,
+/// which the parser allows for.
+fn escape_name(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '%' => escaped.push_str("%25"),
+            '\n' => escaped.push_str("%0A"),
+            '\r' => escaped.push_str("%0D"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+pub(crate) fn unescape(value: &str) -> String {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut rest = value.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        let decoded = (byte == b'%')
+            .then(|| tail.get(..2))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match decoded {
+            Some(decoded) => {
+                bytes.push(decoded);
+                rest = &tail[2..];
+            }
+            None => {
+                bytes.push(byte);
+                rest = tail;
+            }
+        }
     }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// A parse error without line information; `ToDoList` adds it when it knows the line.
+fn parse_error(message: &str) -> Error {
+    Error::Parse { line: None, message: message.to_string() }
 }
 
 impl FromStr for Task {
-    type Err = String;
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        // Peel the metadata fields off the end first; they're collected right to left.
+        let mut line = s;
+        let mut metadata = Vec::new();
+        while let Some((rest, field)) = line.rsplit_once(',') {
+            let Some(pair) = metadata_field(field) else { break };
+            metadata.push(pair);
+            line = rest;
+        }
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
         // We split from the right, because status and importance don't contain commas.
-        let mut parts: Vec<&str> = s.rsplitn(3, ',').collect();
+        let mut parts: Vec<&str> = line.rsplitn(3, ',').collect();
         if parts.len() != 3 {
-            return Err("Incorrect line format: couldn't split into 3 parts from right".to_string());
+            return Err(parse_error("Incorrect line format: couldn't split into 3 parts from right"));
         }
         // Because we used rsplit, the vector is reversed. Let's fix that.
         parts.reverse();
@@ -95,48 +290,55 @@ impl FromStr for Task {
 
         let id_name_parts: Vec<&str> = id_and_name.splitn(2, ',').collect();
         if id_name_parts.len() != 2 {
-            return Err("Incorrect line format: couldn't split id and name".to_string());
+            return Err(parse_error("Incorrect line format: couldn't split id and name"));
         }
 
-        let id = id_name_parts[0].parse::<u32>().map_err(|e| e.to_string())?;
-        let name = id_name_parts[1].to_string();
-        let status = Status::from_str(status_str).map_err(|_| "Invalid Status".to_string())?;
+        let id = id_name_parts[0].parse::<u32>().map_err(|e| parse_error(&e.to_string()))?;
+        let name = unescape(id_name_parts[1]);
+        let status = Status::from_str(status_str).map_err(|_| parse_error("Invalid Status"))?;
         let importance =
-            Importance::from_str(importance_str).map_err(|_| "Invalid Importance".to_string())?;
-
-        Ok(Self {
-            id,
-            name,
-            status,
-            importance,
-        })
+            Importance::from_str(importance_str).map_err(|_| parse_error("Invalid Importance"))?;
+
+        let mut task = Self { id, name, status, importance, ..Self::default() };
+        for (key, value) in metadata.into_iter().rev() {
+            // Fields written by newer versions, or mangled by hand, are kept as they are rather
+            // than losing the task.
+            if task.set_metadata(key, &unescape(value)).is_err() {
+                task.unknown.push((key.to_string(), value.to_string()));
+            }
+        }
+        Ok(task)
     }
 }
 
 #[derive(Default)]
 pub struct ToDoList {
     tasks: Vec<Task>,
+    /// The highest ID new tasks must come after besides the list's own, see `reserve_ids`.
+    reserved: TaskId,
 }
 
 impl ToDoList {
-    pub fn load(path: &str) -> io::Result<Self> {
-        match File::open(path) {
-            Ok(file) => {
-                let reader = BufReader::new(file);
-                let tasks: Vec<Task> = reader
-                    .lines()
-                    .map_while(Result::ok)
-                    .filter_map(|line| Task::from_str(&line).ok())
-                    .collect();
-
-                Ok(Self{ tasks })
+    /// Loads a task list, failing on the first malformed line with its line number rather
+    /// than dropping it, as saving would then erase it. A missing file is an empty list.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut tasks = Vec::new();
+        for (index, line) in content.lines().enumerate().filter(|(_, l)| !l.trim().is_empty()) {
+            match Task::from_str(line) {
+                Ok(task) => tasks.push(task),
+                Err(Error::Parse { message, .. }) => return Err(Error::Parse { line: Some(index + 1), message }),
+                Err(e) => return Err(e),
             }
-            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self{ tasks: vec![] }),
-            Err(e) => Err(e),
         }
+        Ok(Self { tasks, ..Self::default() })
     }
 
-    pub fn save(&self, path: &str) -> io::Result<()> {
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
         let mut file = OpenOptions::new()
             .write(true)
             .create(true)
@@ -150,7 +352,13 @@ impl ToDoList {
     }
 
     fn get_next_id(&self) -> u32 {
-        self.tasks.iter().map(|t| t.id).max().unwrap_or(0) + 1
+        self.tasks.iter().map(|t| t.id).max().unwrap_or(0).max(self.reserved) + 1
+    }
+
+    /// Makes new tasks get IDs after every task in `other` too, such as the archive's, so an
+    /// ID that commits and branches may still refer to is never given to another task.
+    pub fn reserve_ids(&mut self, other: &ToDoList) {
+        self.reserved = other.tasks.iter().map(|t| t.id).max().unwrap_or(0).max(self.reserved);
     }
 
     pub fn add_task(&mut self, name: String, importance: Importance) -> u32 {
@@ -161,6 +369,13 @@ impl ToDoList {
             name,
             status: Status::Pending,
             importance,
+            created: Some(now()),
+            tags: Vec::new(),
+            parent: None,
+            description: None,
+            finished: None,
+            location: None,
+            unknown: Vec::new(),
         };
 
         self.tasks.push(new_task);
@@ -168,35 +383,139 @@ impl ToDoList {
         new_id
     }
 
-    pub fn update_task(&mut self, id: u32, new_status: Option<Status>, new_importance: Option<Importance>) -> bool {
-        if let Some(task) = self.tasks.iter_mut().find(|t| t.id == id) {
-            if let Some(status) = new_status {
-                task.status = status;
-            }
-            if let Some(importance) = new_importance {
-                task.importance = importance;
-            }
-            return true;
+    pub fn update_task(&mut self, id: u32, new_status: Option<Status>, new_importance: Option<Importance>) -> Result<()> {
+        let task = self.tasks.iter_mut().find(|t| t.id == id).ok_or(Error::NotFound(id))?;
+        if let Some(status) = new_status {
+            task.set_status(status);
+        }
+        if let Some(importance) = new_importance {
+            task.importance = importance;
         }
-        false
+        Ok(())
     }
 
-    pub fn remove_task(&mut self, id: u32) -> bool {
+    /// Removes a task. Its subtasks are kept and become top-level tasks.
+    pub fn remove_task(&mut self, id: u32) -> Result<()> {
         let initial_len = self.tasks.len();
         self.tasks.retain(|task| task.id != id);
-        self.tasks.len() < initial_len
+        if self.tasks.len() < initial_len {
+            for task in self.tasks.iter_mut().filter(|task| task.parent == Some(id)) {
+                task.parent = None;
+            }
+            Ok(())
+        } else {
+            Err(Error::NotFound(id))
+        }
+    }
+
+    /// Adds and removes tags on a task. Tags already present aren't duplicated.
+    pub fn tag_task(&mut self, id: u32, add: &[String], remove: &[String]) -> Result<()> {
+        for tag in add {
+            validate_tag(tag)?;
+        }
+        let task = self.tasks.iter_mut().find(|t| t.id == id).ok_or(Error::NotFound(id))?;
+        for tag in add {
+            if !task.tags.contains(tag) {
+                task.tags.push(tag.clone());
+            }
+        }
+        task.tags.retain(|tag| !remove.contains(tag));
+        Ok(())
+    }
+
+    /// Sets a task's description; an empty one removes it.
+    pub fn describe_task(&mut self, id: u32, description: &str) -> Result<()> {
+        let task = self.tasks.iter_mut().find(|t| t.id == id).ok_or(Error::NotFound(id))?;
+        task.description = Some(description.to_string()).filter(|d| !d.is_empty());
+        Ok(())
+    }
+
+    pub fn rename_task(&mut self, id: u32, name: &str) -> Result<()> {
+        let task = self.tasks.iter_mut().find(|t| t.id == id).ok_or(Error::NotFound(id))?;
+        task.name = name.to_string();
+        Ok(())
+    }
+
+    /// Points a task at the comment it tracks, renaming it to the comment's text.
+    pub fn link_task(&mut self, id: u32, name: &str, location: Location) -> Result<()> {
+        let task = self.tasks.iter_mut().find(|t| t.id == id).ok_or(Error::NotFound(id))?;
+        task.name = name.to_string();
+        task.location = Some(location);
+        Ok(())
+    }
+
+    /// Makes `id` a subtask of `parent`, or a top-level task with `None`.
+    pub fn set_parent(&mut self, id: u32, parent: Option<TaskId>) -> Result<()> {
+        self.tasks.iter().find(|t| t.id == id).ok_or(Error::NotFound(id))?;
+        // Walk up from the new parent; reaching `id` means the change would make a cycle.
+        let mut ancestor = parent;
+        while let Some(ancestor_id) = ancestor {
+            if ancestor_id == id {
+                return Err(Error::InvalidParent { id, parent: parent.unwrap_or(id) });
+            }
+            ancestor = self.tasks.iter().find(|t| t.id == ancestor_id).ok_or(Error::NotFound(ancestor_id))?.parent;
+        }
+        if let Some(task) = self.tasks.iter_mut().find(|t| t.id == id) {
+            task.parent = parent;
+        }
+        Ok(())
     }
 
-    pub fn update_statuses_from_logs(&mut self, logs_content: &str) -> bool {
-        let mut tasks_updated = false;
+    /// Finishes every open task whose name appears in the logs. Returns the IDs it finished.
+    pub fn update_statuses_from_logs(&mut self, logs_content: &str) -> Vec<TaskId> {
+        let mut finished = Vec::new();
         for task in &mut self.tasks {
-            if task.status == Status::Pending && logs_content.contains(&task.name) {
-                println!("Task '{}' finished!", task.name);
-                task.status = Status::Finished;
-                tasks_updated = true;
+            if task.status != Status::Finished && logs_content.contains(&task.name) {
+                task.set_status(Status::Finished);
+                finished.push(task.id);
             }
         }
-        tasks_updated
+        finished
+    }
+
+    /// Marks a pending task as started. Returns false if it's missing or already past pending.
+    pub fn start_task(&mut self, id: u32) -> bool {
+        match self.tasks.iter_mut().find(|t| t.id == id) {
+            Some(task) if task.status == Status::Pending => {
+                task.set_status(Status::Started);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Marks a task as finished. Returns false if it's missing or already finished.
+    pub fn finish_task(&mut self, id: u32) -> bool {
+        match self.tasks.iter_mut().find(|t| t.id == id) {
+            Some(task) if task.status != Status::Finished => {
+                task.set_status(Status::Finished);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Removes the given tasks and returns them, in list order.
+    pub fn take(&mut self, ids: &[TaskId]) -> Vec<Task> {
+        let (taken, kept) = std::mem::take(&mut self.tasks).into_iter().partition(|t| ids.contains(&t.id));
+        self.tasks = kept;
+        taken
+    }
+
+    /// Adds an existing task, such as one coming back from the archive. It keeps its ID
+    /// unless another task has it or it is 0, in which case it gets the next free one.
+    /// Returns the ID.
+    pub fn insert(&mut self, mut task: Task) -> TaskId {
+        if task.id == 0 || self.get(task.id).is_some() {
+            task.id = self.get_next_id();
+        }
+        let id = task.id;
+        self.tasks.push(task);
+        id
+    }
+
+    pub fn get(&self, id: u32) -> Option<&Task> {
+        self.tasks.iter().find(|t| t.id == id)
     }
 
     pub fn is_empty(&self) -> bool {
@@ -212,6 +531,7 @@ impl ToDoList {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::fs::File;
     use tempfile;
 
     // Test 1: Successful parsing of a valid task line
@@ -254,14 +574,14 @@ mod tests {
 
         // Assert
         assert!(result.is_err());
-        assert_eq!(result.err().unwrap(), "Invalid Status");
+        assert_eq!(result.err().unwrap().to_string(), "Invalid Status");
     }
 
     // Test 4: Add a task and verify it's in the list
     #[test]
     fn test_todolist_add_task() {
         // Arrange
-        let mut list = ToDoList { tasks: vec![] };
+        let mut list = ToDoList::default();
         let name = "Write unit tests".to_string();
 
         // Act
@@ -279,7 +599,7 @@ mod tests {
     #[test]
     fn test_todolist_get_next_id_empty() {
         // Arrange
-        let list = ToDoList { tasks: vec![] };
+        let list = ToDoList::default();
 
         // Act
         let next_id = list.get_next_id();
@@ -293,10 +613,10 @@ mod tests {
     fn test_todolist_get_next_id_populated() {
         // Arrange
         let tasks = vec![
-            Task { id: 1, name: "t1".into(), status: Status::Pending, importance: Importance::Normal },
-            Task { id: 5, name: "t5".into(), status: Status::Finished, importance: Importance::Urgent },
+            Task { id: 1, name: "t1".into(), status: Status::Pending, importance: Importance::Normal, ..Default::default() },
+            Task { id: 5, name: "t5".into(), status: Status::Finished, importance: Importance::Urgent, ..Default::default() },
         ];
-        let list = ToDoList { tasks };
+        let list = ToDoList { tasks, ..Default::default() };
 
         // Act
         let next_id = list.get_next_id();
@@ -310,15 +630,15 @@ mod tests {
     fn test_todolist_update_task_success() {
         // Arrange
         let tasks = vec![
-            Task { id: 1, name: "t1".into(), status: Status::Pending, importance: Importance::Normal },
+            Task { id: 1, name: "t1".into(), status: Status::Pending, importance: Importance::Normal, ..Default::default() },
         ];
-        let mut list = ToDoList { tasks };
+        let mut list = ToDoList { tasks, ..Default::default() };
 
         // Act
         let result = list.update_task(1, Some(Status::Finished), Some(Importance::Urgent));
 
         // Assert
-        assert!(result);
+        assert!(result.is_ok());
         assert_eq!(list.tasks[0].status, Status::Finished);
         assert_eq!(list.tasks[0].importance, Importance::Urgent);
     }
@@ -327,13 +647,13 @@ mod tests {
     #[test]
     fn test_todolist_update_task_not_found() {
         // Arrange
-        let mut list = ToDoList { tasks: vec![] };
+        let mut list = ToDoList::default();
 
         // Act
         let result = list.update_task(99, Some(Status::Finished), None);
 
         // Assert
-        assert!(!result);
+        assert!(matches!(result, Err(Error::NotFound(99))));
     }
 
     // Test 9: Successfully remove an existing task
@@ -341,15 +661,15 @@ mod tests {
     fn test_todolist_remove_task_success() {
         // Arrange
         let tasks = vec![
-            Task { id: 1, name: "t1".into(), status: Status::Pending, importance: Importance::Normal },
+            Task { id: 1, name: "t1".into(), status: Status::Pending, importance: Importance::Normal, ..Default::default() },
         ];
-        let mut list = ToDoList { tasks };
+        let mut list = ToDoList { tasks, ..Default::default() };
 
         // Act
         let result = list.remove_task(1);
 
         // Assert
-        assert!(result);
+        assert!(result.is_ok());
         assert!(list.tasks.is_empty());
     }
 
@@ -358,15 +678,15 @@ mod tests {
     fn test_todolist_remove_task_not_found() {
         // Arrange
         let tasks = vec![
-            Task { id: 1, name: "t1".into(), status: Status::Pending, importance: Importance::Normal },
+            Task { id: 1, name: "t1".into(), status: Status::Pending, importance: Importance::Normal, ..Default::default() },
         ];
-        let mut list = ToDoList { tasks };
+        let mut list = ToDoList { tasks, ..Default::default() };
 
         // Act
         let result = list.remove_task(99);
 
         // Assert
-        assert!(!result);
+        assert!(matches!(result, Err(Error::NotFound(99))));
         assert_eq!(list.tasks.len(), 1);
     }
 
@@ -376,16 +696,16 @@ mod tests {
         // Arrange
         let task_name = "feat: Implement the core logic".to_string();
         let tasks = vec![
-            Task { id: 1, name: task_name.clone(), status: Status::Pending, importance: Importance::Normal },
+            Task { id: 1, name: task_name.clone(), status: Status::Pending, importance: Importance::Normal, ..Default::default() },
         ];
-        let mut list = ToDoList { tasks };
+        let mut list = ToDoList { tasks, ..Default::default() };
         let logs_content = "commit 1234\nAuthor: a\nDate: now\n\n    feat: Implement the core logic\n";
 
         // Act
-        let updated = list.update_statuses_from_logs(logs_content);
+        let finished = list.update_statuses_from_logs(logs_content);
 
         // Assert
-        assert!(updated);
+        assert_eq!(finished, vec![1]);
         assert_eq!(list.tasks[0].status, Status::Finished);
     }
 
@@ -395,16 +715,16 @@ mod tests {
         // Arrange
         let task_name = "fix: A critical bug".to_string();
         let tasks = vec![
-            Task { id: 1, name: task_name.clone(), status: Status::Finished, importance: Importance::Urgent },
+            Task { id: 1, name: task_name.clone(), status: Status::Finished, importance: Importance::Urgent, ..Default::default() },
         ];
-        let mut list = ToDoList { tasks };
+        let mut list = ToDoList { tasks, ..Default::default() };
         let logs_content = "commit 5678\nAuthor: b\nDate: past\n\n    fix: A critical bug\n";
 
         // Act
-        let updated = list.update_statuses_from_logs(logs_content);
+        let finished = list.update_statuses_from_logs(logs_content);
 
         // Assert
-        assert!(!updated);
+        assert!(finished.is_empty());
         assert_eq!(list.tasks[0].status, Status::Finished);
     }
 
@@ -413,16 +733,16 @@ mod tests {
     fn test_update_statuses_from_logs_not_in_logs() {
         // Arrange
         let tasks = vec![
-            Task { id: 1, name: "docs: Update README".into(), status: Status::Pending, importance: Importance::Normal },
+            Task { id: 1, name: "docs: Update README".into(), status: Status::Pending, importance: Importance::Normal, ..Default::default() },
         ];
-        let mut list = ToDoList { tasks };
+        let mut list = ToDoList { tasks, ..Default::default() };
         let logs_content = "commit 9012\nAuthor: c\nDate: future\n\n    chore: Release new version\n";
 
         // Act
-        let updated = list.update_statuses_from_logs(logs_content);
+        let finished = list.update_statuses_from_logs(logs_content);
         
         // Assert
-        assert!(!updated);
+        assert!(finished.is_empty());
         assert_eq!(list.tasks[0].status, Status::Pending);
     }
 
@@ -546,9 +866,9 @@ mod tests {
 
             // Arrange
 
-            let tasks = vec![Task { id: 1, name: "t1".into(), status: Status::Pending, importance: Importance::Normal }];
+            let tasks = vec![Task { id: 1, name: "t1".into(), status: Status::Pending, importance: Importance::Normal, ..Default::default() }];
 
-            let mut list = ToDoList { tasks };
+            let mut list = ToDoList { tasks, ..Default::default() };
 
     
 
@@ -560,7 +880,7 @@ mod tests {
 
             // Assert
 
-            assert!(result);
+            assert!(result.is_ok());
 
             assert_eq!(list.tasks[0].status, Status::Pending); // Status should not change
 
@@ -660,7 +980,7 @@ mod tests {
 
     
 
-        // Test 21: Load from a file with mixed valid and invalid lines
+        // Test 21: Loading a file with a malformed line fails rather than dropping it
 
         #[test]
 
@@ -686,19 +1006,13 @@ mod tests {
 
             // Act
 
-            let list = ToDoList::load(path_str).unwrap();
+            let result = ToDoList::load(path_str);
 
     
 
             // Assert
 
-            assert_eq!(list.tasks.len(), 3);
-
-            assert_eq!(list.tasks[0].id, 1);
-
-            assert_eq!(list.tasks[1].id, 2);
-
-            assert_eq!(list.tasks[2].id, 3);
+            assert!(matches!(result, Err(Error::Parse { line: Some(2), .. })));
 
         }
 
@@ -726,10 +1040,209 @@ mod tests {
 
             assert!(result.is_err());
 
-            assert_eq!(result.err().unwrap(), "Incorrect line format: couldn't split into 3 parts from right");
+            assert_eq!(result.err().unwrap().to_string(), "Incorrect line format: couldn't split into 3 parts from right");
 
         }
 
-    }
+    
+        // Test 23: Start only moves pending tasks, finish closes started ones
+        #[test]
+        fn test_todolist_start_and_finish_task() {
+            // Arrange
+            let mut list = ToDoList::default();
+            list.add_task("Task".to_string(), Importance::Normal);
+
+            // Act
+            let started = list.start_task(1);
+            let started_again = list.start_task(1);
+            let finished = list.finish_task(1);
+
+            // Assert
+            assert!(started);
+            assert!(!started_again);
+            assert!(finished);
+            assert_eq!(list.tasks[0].status, Status::Finished);
+        }
+
+        // Test 24: Parse the started status
+        #[test]
+        fn test_task_from_str_started() {
+            // Arrange
+            let line = "4,Work in progress,started,important";
+
+            // Act
+            let task = Task::from_str(line).unwrap();
+
+            // Assert
+            assert_eq!(task.status, Status::Started);
+            assert_eq!(task.to_csv_line(), line);
+        }
+
+        // Test 25: Loading skips blank lines but counts them in the reported line number
+        #[test]
+        fn test_todolist_load_reports_line() {
+            // Arrange
+            let temp_file = tempfile::NamedTempFile::new().unwrap();
+            let path_str = temp_file.path().to_str().unwrap();
+            let mut file = File::create(path_str).unwrap();
+            writeln!(file, "1,Valid task,pending,normal").unwrap();
+            writeln!(file).unwrap();
+            writeln!(file, "2,Task with,commas,pending,important").unwrap();
+            writeln!(file, "3,Broken task,done,normal").unwrap();
+
+            // Act
+            let result = ToDoList::load(path_str);
+
+            // Assert
+            assert!(matches!(result, Err(Error::Parse { line: Some(4), .. })));
+        }
+
+        // Test 26: Status and importance parsing report the offending value
+        #[test]
+        fn test_status_and_importance_errors() {
+            assert!(matches!("done".parse::<Status>(), Err(Error::InvalidStatus(s)) if s == "done"));
+            assert!(matches!("high".parse::<Importance>(), Err(Error::InvalidImportance(s)) if s == "high"));
+        }
+
+        // Test 27: Metadata fields round-trip, escaped, after the importance
+        #[test]
+        fn test_task_metadata_round_trip() {
+            // Arrange
+            let task = Task {
+                id: 4,
+                name: "Split, then merge".into(),
+                created: Some(1_700_000_000),
+                tags: vec!["api".into(), "50%".into()],
+                parent: Some(2),
+                description: Some("Rebase, then\nmerge".into()),
+                finished: Some(1_700_000_100),
+                location: Some(Location { path: "src/a,b.rs".into(), line: 12 }),
+                ..Default::default()
+            };
+
+            // Act
+            let line = task.to_csv_line();
+            let parsed = Task::from_str(&line).unwrap();
+
+            // Assert
+            assert_eq!(line, "4,Split, then merge,pending,normal,created=1700000000,tags=api;50%25,parent=2,description=Rebase%2C then%0Amerge,finished=1700000100,location=src/a%2Cb.rs:12");
+            assert_eq!(parsed, task);
+        }
+
+        // Test 28: Lines without metadata still parse, and unknown or unreadable fields are kept as written
+        #[test]
+        fn test_task_metadata_optional() {
+            // Arrange
+            let legacy = "7,Old task,finished,urgent";
+            let newer = "7,Old task,finished,urgent,created=soon,tags=api,due=2030-01-01%2C9am";
+
+            // Act
+            let legacy_task = Task::from_str(legacy).unwrap();
+            let newer_task = Task::from_str(newer).unwrap();
+
+            // Assert
+            assert_eq!(legacy_task.created, None);
+            assert!(legacy_task.tags.is_empty());
+            assert_eq!(newer_task.created, None);
+            assert_eq!(newer_task.tags, vec!["api".to_string()]);
+            assert_eq!(newer_task.to_csv_line(), "7,Old task,finished,urgent,tags=api,created=soon,due=2030-01-01%2C9am");
+        }
+
+        // Test 29: Tagging validates tags and doesn't duplicate them
+        #[test]
+        fn test_todolist_tag_task() {
+            // Arrange
+            let mut list = ToDoList::default();
+            list.add_task("Tag me".into(), Importance::Normal);
+
+            // Act
+            list.tag_task(1, &["api".into(), "ui".into()], &[]).unwrap();
+            list.tag_task(1, &["api".into()], &["ui".into()]).unwrap();
+            let invalid = list.tag_task(1, &["two words".into()], &[]);
+
+            // Assert
+            assert_eq!(list.tasks[0].tags, vec!["api".to_string()]);
+            assert!(matches!(invalid, Err(Error::InvalidTag(_))));
+        }
+
+        // Test 30: Parents must exist and can't form cycles; removing a parent frees its subtasks
+        #[test]
+        fn test_todolist_set_parent() {
+            // Arrange
+            let mut list = ToDoList::default();
+            list.add_task("Release".into(), Importance::Normal);
+            list.add_task("Changelog".into(), Importance::Normal);
+
+            // Act
+            list.set_parent(2, Some(1)).unwrap();
+            let cycle = list.set_parent(1, Some(2));
+            let missing = list.set_parent(2, Some(9));
+            list.remove_task(1).unwrap();
+
+            // Assert
+            assert!(matches!(cycle, Err(Error::InvalidParent { id: 1, parent: 2 })));
+            assert!(matches!(missing, Err(Error::NotFound(9))));
+            assert_eq!(list.tasks[0].parent, None);
+        }
+
+        // Test 31: Finishing records the time, reopening clears it, and taken tasks can come back
+        #[test]
+        fn test_todolist_finish_time_take_and_insert() {
+            // Arrange
+            let mut list = ToDoList::default();
+            list.add_task("Ship".into(), Importance::Normal);
+            list.add_task("Celebrate".into(), Importance::Normal);
+
+            // Act
+            list.update_task(1, Some(Status::Finished), None).unwrap();
+            let finished = list.tasks[0].finished;
+            let taken = list.take(&[1]);
+            list.add_task("Plan".into(), Importance::Normal);
+            let reinserted = list.insert(taken[0].clone());
+            let renumbered = list.insert(list.tasks[0].clone());
+            list.update_task(reinserted, Some(Status::Pending), None).unwrap();
+
+            // Assert
+            assert!(finished.is_some());
+            assert_eq!(taken.len(), 1);
+            assert_eq!(reinserted, 1);
+            assert_eq!(renumbered, 4);
+            assert_eq!(list.get(1).unwrap().finished, None);
+            assert_eq!(list.get(1).unwrap().finished_ago(now()), None);
+        }
+
+        // Test 32: New IDs also come after reserved ones, such as the archive's
+        #[test]
+        fn test_todolist_get_next_id_reserved() {
+            // Arrange
+            let mut list = ToDoList::default();
+            list.add_task("Kept".into(), Importance::Normal);
+            let mut archive = ToDoList::default();
+            archive.insert(Task { id: 9, name: "Archived".into(), ..Default::default() });
+
+            // Act
+            list.reserve_ids(&archive);
+            let id = list.add_task("New".into(), Importance::Normal);
 
-    
\ No newline at end of file
+            // Assert
+            assert_eq!(id, 10);
+        }
+
+        // Test 33: Names with line breaks and percent signs survive a save and load
+        #[test]
+        fn test_todolist_name_round_trip() {
+            // Arrange
+            let temp_file = tempfile::NamedTempFile::new().unwrap();
+            let mut list = ToDoList::default();
+            list.add_task("foo\nbar\r, 50%25 done".into(), Importance::Normal);
+
+            // Act
+            list.save(temp_file.path()).unwrap();
+            let content = std::fs::read_to_string(temp_file.path()).unwrap();
+            let loaded = ToDoList::load(temp_file.path()).unwrap();
+
+            // Assert
+            assert!(content.starts_with("1,foo%0Abar%0D, 50%2525 done,pending,normal"));
+            assert_eq!(loaded.tasks()[0].name, "foo\nbar\r, 50%25 done");
+        }
+    }
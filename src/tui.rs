@@ -0,0 +1,387 @@
+use std::io::{self, IsTerminal};
+
+use ratatui::backend::Backend;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState};
+use ratatui::{Frame, Terminal};
+
+use crate::config::Config;
+use crate::error::{Error, Result};
+use crate::query::Query;
+use crate::store::Store;
+use crate::task::{self, Importance, Status, Task, TaskId, ToDoList};
+
+const HELP: &str = "j/k move  s status  i importance  a add  e edit  d delete  / filter  q quit";
+
+/// What the line at the bottom is asking for.
+#[derive(PartialEq, Eq, Debug, Clone)]
+enum Prompt {
+    Add,
+    Rename(TaskId),
+    Filter,
+    Delete(TaskId),
+}
+
+/// The state of `rudden tui`: the list, what's selected and any prompt being answered.
+/// Every change is made to the store's list as it is on disk and saved straight away.
+pub struct App<'a> {
+    list: ToDoList,
+    store: Store,
+    config: &'a Config,
+    filter: Option<Query>,
+    filter_text: String,
+    /// Index into the visible tasks.
+    selected: usize,
+    prompt: Option<(Prompt, String)>,
+    message: Option<String>,
+    quit: bool,
+}
+
+impl<'a> App<'a> {
+    pub fn new(list: ToDoList, store: Store, config: &'a Config) -> Self {
+        Self { list, store, config, filter: None, filter_text: String::new(), selected: 0, prompt: None, message: None, quit: false }
+    }
+
+    pub fn list(&self) -> &ToDoList {
+        &self.list
+    }
+
+    pub fn into_list(self) -> ToDoList {
+        self.list
+    }
+
+    pub fn should_quit(&self) -> bool {
+        self.quit
+    }
+
+    /// The tasks the filter lets through, in list order.
+    fn visible(&self) -> Vec<&Task> {
+        let now = task::now();
+        self.list.tasks().iter().filter(|t| self.filter.as_ref().is_none_or(|query| query.matches(t, now))).collect()
+    }
+
+    fn selected_task(&self) -> Option<TaskId> {
+        self.visible().get(self.selected).map(|t| t.id)
+    }
+
+    /// Makes a change under the store's lock: reads the list afresh, so changes made
+    /// elsewhere since aren't lost, applies `edit` and saves. Keeps the selection on a visible
+    /// task. If the task was removed meanwhile, says so on the bottom line and returns `None`.
+    fn change<T>(&mut self, edit: impl FnOnce(&mut ToDoList) -> Result<T>) -> Result<Option<T>> {
+        let _lock = self.store.lock()?;
+        self.list = ToDoList::load(self.store.tasks_path())?;
+        let result = match edit(&mut self.list) {
+            Ok(result) => Some(result),
+            Err(e @ Error::NotFound(_)) => {
+                self.message = Some(e.to_string());
+                None
+            }
+            Err(e) => return Err(e),
+        };
+        self.list.save(self.store.tasks_path())?;
+        self.selected = self.selected.min(self.visible().len().saturating_sub(1));
+        Ok(result)
+    }
+
+    /// Handles one key press. User mistakes, like an invalid filter, are shown on the bottom
+    /// line; only failing to save is an error.
+    pub fn handle_key(&mut self, key: KeyEvent) -> Result<()> {
+        if key.kind != KeyEventKind::Press {
+            return Ok(());
+        }
+        self.message = None;
+        if let Some((prompt, text)) = self.prompt.take() {
+            return self.handle_prompt(prompt, text, key);
+        }
+        let count = self.visible().len();
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => self.quit = true,
+            KeyCode::Char('j') | KeyCode::Down => self.selected = (self.selected + 1).min(count.saturating_sub(1)),
+            KeyCode::Char('k') | KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Char('g') | KeyCode::Home => self.selected = 0,
+            KeyCode::Char('G') | KeyCode::End => self.selected = count.saturating_sub(1),
+            KeyCode::Char('a') => self.prompt = Some((Prompt::Add, String::new())),
+            KeyCode::Char('/') => self.prompt = Some((Prompt::Filter, self.filter_text.clone())),
+            KeyCode::Char('e') => {
+                if let Some(id) = self.selected_task() {
+                    let name = self.list.get(id).map(|t| t.name.clone()).unwrap_or_default();
+                    self.prompt = Some((Prompt::Rename(id), name));
+                }
+            }
+            KeyCode::Char('d') => {
+                if let Some(id) = self.selected_task() {
+                    self.prompt = Some((Prompt::Delete(id), String::new()));
+                }
+            }
+            KeyCode::Char('s') => {
+                if let Some(id) = self.selected_task() {
+                    self.change(|list| {
+                        let next = match list.get(id).ok_or(Error::NotFound(id))?.status {
+                            Status::Pending => Status::Started,
+                            Status::Started => Status::Finished,
+                            Status::Finished => Status::Pending,
+                        };
+                        list.update_task(id, Some(next), None)
+                    })?;
+                }
+            }
+            KeyCode::Char('i') => {
+                if let Some(id) = self.selected_task() {
+                    self.change(|list| {
+                        let next = match list.get(id).ok_or(Error::NotFound(id))?.importance {
+                            Importance::Normal => Importance::Important,
+                            Importance::Important => Importance::Urgent,
+                            Importance::Urgent => Importance::Normal,
+                        };
+                        list.update_task(id, None, Some(next))
+                    })?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn handle_prompt(&mut self, prompt: Prompt, mut text: String, key: KeyEvent) -> Result<()> {
+        if let Prompt::Delete(id) = prompt {
+            if key.code == KeyCode::Char('y') && self.change(|list| list.remove_task(id))?.is_some() {
+                self.message = Some(format!("Removed task {}.", id));
+            }
+            return Ok(());
+        }
+        match key.code {
+            KeyCode::Esc => {}
+            KeyCode::Enter => self.submit(prompt, text.trim())?,
+            KeyCode::Backspace => {
+                text.pop();
+                self.prompt = Some((prompt, text));
+            }
+            KeyCode::Char(c) => {
+                text.push(c);
+                self.prompt = Some((prompt, text));
+            }
+            _ => self.prompt = Some((prompt, text)),
+        }
+        Ok(())
+    }
+
+    fn submit(&mut self, prompt: Prompt, text: &str) -> Result<()> {
+        match prompt {
+            Prompt::Add if !text.is_empty() => {
+                let (archive, importance) = (self.store.archive_path(), self.config.default_importance());
+                let added = self.change(|list| {
+                    // Archived tasks keep their IDs, so the new one mustn't get one.
+                    list.reserve_ids(&ToDoList::load(archive)?);
+                    Ok(list.add_task(text.to_string(), importance))
+                })?;
+                if let Some(id) = added {
+                    self.selected = self.visible().iter().position(|t| t.id == id).unwrap_or(self.selected);
+                }
+            }
+            Prompt::Rename(id) if !text.is_empty() => {
+                self.change(|list| list.rename_task(id, text))?;
+            }
+            Prompt::Filter => match Query::parse(text, |name| self.config.saved_query(name)) {
+                Ok(query) => {
+                    self.filter = (!text.is_empty()).then_some(query);
+                    self.filter_text = text.to_string();
+                    self.selected = 0;
+                }
+                Err(e) => self.message = Some(e.to_string()),
+            },
+            _ => {}
+        }
+        Ok(())
+    }
+
+    pub fn draw(&self, frame: &mut Frame) {
+        let [list_area, status_area] = Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+        let visible = self.visible();
+
+        let rows = visible.iter().map(|task| {
+            let style = match (&task.status, &task.importance) {
+                (Status::Finished, _) => Style::default().fg(Color::DarkGray),
+                (_, Importance::Urgent) => Style::default().fg(Color::Red),
+                (Status::Started, _) => Style::default().fg(Color::Yellow),
+                _ => Style::default(),
+            };
+            let tags: String = task.tags.iter().map(|tag| format!(" #{}", tag)).collect();
+            Row::new(vec![
+                Cell::from(task.id.to_string()),
+                Cell::from(task.status.to_string()),
+                Cell::from(task.importance.to_string()),
+                Cell::from(Line::from(vec![Span::raw(task.name.clone()), Span::styled(tags, Style::default().fg(Color::Cyan))])),
+            ])
+            .style(style)
+        });
+        let title = match self.filter_text.as_str() {
+            "" => format!(" rudden: {} tasks ", visible.len()),
+            filter => format!(" rudden: {} of {} tasks matching {} ", visible.len(), self.list.tasks().len(), filter),
+        };
+        let table = Table::new(rows, [Constraint::Length(4), Constraint::Length(9), Constraint::Length(10), Constraint::Fill(1)])
+            .header(Row::new(["ID", "Status", "Importance", "Name"]).style(Style::default().add_modifier(Modifier::BOLD)))
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        let mut state = TableState::default().with_selected((!visible.is_empty()).then_some(self.selected));
+        frame.render_stateful_widget(table, list_area, &mut state);
+
+        let status = match (&self.prompt, &self.message) {
+            (Some((Prompt::Add, text)), _) => format!("New task: {}", text),
+            (Some((Prompt::Rename(id), text)), _) => format!("Rename task {}: {}", id, text),
+            (Some((Prompt::Filter, text)), _) => format!("Filter: {}", text),
+            (Some((Prompt::Delete(id), _)), _) => format!("Remove task {}? [y/N]", id),
+            (None, Some(message)) => message.clone(),
+            (None, None) => HELP.to_string(),
+        };
+        frame.render_widget(Paragraph::new(status), status_area);
+    }
+}
+
+/// Runs the TUI until the user quits, returning the list as it was left.
+pub fn run<B: Backend>(terminal: &mut Terminal<B>, list: ToDoList, store: Store, config: &Config) -> Result<ToDoList> {
+    let mut app = App::new(list, store, config);
+    while !app.should_quit() {
+        terminal.draw(|frame| app.draw(frame))?;
+        if let Event::Key(key) = event::read()? {
+            app.handle_key(key)?;
+        }
+    }
+    Ok(app.into_list())
+}
+
+/// Takes over the terminal for the TUI, and gives it back even if saving fails.
+pub fn open(store: Store, config: &Config) -> Result<ToDoList> {
+    if !io::stdout().is_terminal() {
+        return Err(io::Error::new(io::ErrorKind::Unsupported, "rudden tui needs a terminal").into());
+    }
+    let list = {
+        let _lock = store.lock()?;
+        ToDoList::load(store.tasks_path())?
+    };
+    let mut terminal = ratatui::try_init()?;
+    let result = run(&mut terminal, list, store, config);
+    ratatui::restore();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+
+    fn press(app: &mut App, keys: &str) {
+        for c in keys.chars() {
+            let code = match c {
+                '\n' => KeyCode::Enter,
+                c => KeyCode::Char(c),
+            };
+            app.handle_key(KeyEvent::from(code)).unwrap();
+        }
+    }
+
+    fn screen(app: &App) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(60, 8)).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+        let buffer = terminal.backend().buffer();
+        let width = buffer.area.width as usize;
+        let symbols: Vec<&str> = buffer.content().iter().map(|cell| cell.symbol()).collect();
+        symbols.chunks(width).map(|line| line.concat().trim_end().to_string()).collect::<Vec<_>>().join("\n")
+    }
+
+    fn app<'a>(config: &'a Config, dir: &tempfile::TempDir) -> App<'a> {
+        let store = Store { dir: dir.path().to_path_buf() };
+        let mut list = ToDoList::default();
+        list.add_task("Write docs".to_string(), Importance::Normal);
+        list.add_task("Fix login".to_string(), Importance::Normal);
+        list.tag_task(2, &["auth".to_string()], &[]).unwrap();
+        list.save(store.tasks_path()).unwrap();
+        App::new(list, store, config)
+    }
+
+    // Test 1: Keys move the selection, change status and importance, and save each change
+    #[test]
+    fn test_tui_edits_selected_task() {
+        // Arrange
+        let config = Config::default();
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = app(&config, &dir);
+
+        // Act
+        press(&mut app, "jssi");
+
+        // Assert
+        let task = app.list().get(2).unwrap();
+        assert_eq!((&task.status, &task.importance), (&Status::Finished, &Importance::Important));
+        let saved = ToDoList::load(dir.path().join(crate::store::TASKS_FILE)).unwrap();
+        assert_eq!(saved.get(2).unwrap().status, Status::Finished);
+    }
+
+    // Test 2: Adding, renaming and deleting go through prompts on the bottom line
+    #[test]
+    fn test_tui_prompts() {
+        // Arrange
+        let config = Config::default();
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = app(&config, &dir);
+
+        // Act
+        press(&mut app, "aShip it");
+        let prompting = screen(&app);
+        press(&mut app, "\n");
+        press(&mut app, "ggdy");
+        press(&mut app, "e!\n");
+
+        // Assert
+        assert!(prompting.ends_with("New task: Ship it"));
+        let names: Vec<&str> = app.list().tasks().iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, vec!["Fix login!", "Ship it"]);
+    }
+
+    // Test 3: Filters narrow the list, and invalid ones are reported without quitting
+    #[test]
+    fn test_tui_filter() {
+        // Arrange
+        let config = Config::default();
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = app(&config, &dir);
+
+        // Act
+        press(&mut app, "/login\n");
+        let filtered = screen(&app);
+        press(&mut app, "/status:done\n");
+        let invalid = screen(&app);
+
+        // Assert
+        assert!(filtered.contains("1 of 2 tasks matching login"));
+        assert!(filtered.contains("Fix login #auth"));
+        assert!(!filtered.contains("Write docs"));
+        assert!(invalid.lines().last().unwrap().contains("status"));
+        assert!(!app.should_quit());
+    }
+
+    // Test 4: Changes are made to the list on disk, keeping what was saved meanwhile
+    #[test]
+    fn test_tui_keeps_changes_made_elsewhere() {
+        // Arrange
+        let config = Config::default();
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = app(&config, &dir);
+        let path = dir.path().join(crate::store::TASKS_FILE);
+        let mut elsewhere = ToDoList::load(&path).unwrap();
+        elsewhere.add_task("Added elsewhere".to_string(), Importance::Normal);
+        elsewhere.save(&path).unwrap();
+
+        // Act
+        press(&mut app, "s");
+
+        // Assert
+        let saved = ToDoList::load(&path).unwrap();
+        assert_eq!(saved.tasks().len(), 3);
+        assert_eq!(saved.get(1).unwrap().status, Status::Started);
+        assert_eq!(app.list().tasks().len(), 3);
+    }
+}
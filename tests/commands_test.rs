@@ -1,7 +1,19 @@
-use rudden::cli::{AddArgs, RmArgs, UpdateArgs};
+use rudden::cli::{AddArgs, ArchiveArgs, BranchArgs, CommitMsgArgs, ConfigAction, ConfigArgs, DashArgs, HooksAction, HooksArgs, ImportArgs, RmArgs, ScanArgs, SearchArgs, ServeArgs, ShowArgs, StartArgs, UnarchiveArgs, UpdateArgs};
+use clap::Parser;
+use rudden::cli::Cli;
 use rudden::commands;
+use rudden::config::Config;
+use rudden::hooks::{Hook, HookState};
+use rudden::import::ImportFormat;
+use rudden::outcome::{AddOutcome, CheckOutcome, HookStatus, HooksOutcome, InitOutcome, RemoveOutcome, StartOutcome, TaskRef, UpdateOutcome};
+use rudden::render::Render;
+use rudden::serve::Api;
+use rudden::store::Store;
+use rudden::{Error, Session};
 use rudden::task::{Importance, Status, ToDoList};
 use std::fs;
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream};
 
 // Test 1: Successfully add a task
 #[test]
@@ -11,14 +23,15 @@ fn test_add_task_success() {
     let args = AddArgs {
         message: "Test this function".to_string(),
         importance: Some("urgent".to_string()),
+        ..Default::default()
     };
 
     // Act
-    let result = commands::add_task(&mut to_do_list, &args);
+    let result = commands::add_task(&mut to_do_list, &args, &Config::default());
 
     // Assert
     assert!(result.is_ok());
-    assert_eq!(result.unwrap(), "Successfully added task with ID: 1");
+    assert_eq!(result.unwrap(), AddOutcome { id: 1 });
     assert_eq!(to_do_list.tasks().len(), 1);
     let task = &to_do_list.tasks()[0];
     assert_eq!(task.name, "Test this function");
@@ -33,14 +46,16 @@ fn test_add_task_invalid_importance() {
     let args = AddArgs {
         message: "A task".to_string(),
         importance: Some("critical".to_string()),
+        ..Default::default()
     };
 
     // Act
-    let result = commands::add_task(&mut to_do_list, &args);
+    let result = commands::add_task(&mut to_do_list, &args, &Config::default());
 
     // Assert
-    assert!(result.is_err());
-    assert_eq!(result.err().unwrap(), "'critical' is not a valid importance. Use 'normal', 'important', or 'urgent'.");
+    let error = result.err().unwrap();
+    assert!(matches!(error, Error::InvalidImportance(ref value) if value == "critical"));
+    assert_eq!(error.to_string(), "'critical' is not a valid importance. Use 'normal', 'important', or 'urgent'.");
     assert!(to_do_list.is_empty());
 }
 
@@ -51,17 +66,18 @@ fn test_update_task_success() {
     let mut to_do_list = ToDoList::default();
     to_do_list.add_task("Initial Task".to_string(), Importance::Normal); // ID will be 1
     let args = UpdateArgs {
-        id: 1,
+        tasks: vec![1.into()],
         status: Some("finished".to_string()),
         importance: Some("urgent".to_string()),
+        ..Default::default()
     };
 
     // Act
-    let result = commands::update_task(&mut to_do_list, &args);
+    let result = commands::update_task(&mut to_do_list, &args, &Config::default());
 
     // Assert
     assert!(result.is_ok());
-    assert_eq!(result.unwrap(), "Successfully updated task with ID: 1");
+    assert_eq!(result.unwrap(), UpdateOutcome { tasks: vec![TaskRef { id: 1, name: "Initial Task".to_string() }] });
     let task = &to_do_list.tasks()[0];
     assert_eq!(task.status, Status::Finished);
     assert_eq!(task.importance, Importance::Urgent);
@@ -73,17 +89,19 @@ fn test_update_task_not_found() {
     // Arrange
     let mut to_do_list = ToDoList::default();
     let args = UpdateArgs {
-        id: 99,
+        tasks: vec![99.into()],
         status: Some("pending".to_string()),
         importance: None,
+        ..Default::default()
     };
 
     // Act
-    let result = commands::update_task(&mut to_do_list, &args);
+    let result = commands::update_task(&mut to_do_list, &args, &Config::default());
 
     // Assert
-    assert!(result.is_err());
-    assert_eq!(result.err().unwrap(), "Task with ID 99 not found.");
+    let error = result.err().unwrap();
+    assert!(matches!(error, Error::NotFound(99)));
+    assert_eq!(error.to_string(), "Task with ID 99 not found.");
 }
 
 // Test 5: Successfully remove a task
@@ -92,14 +110,14 @@ fn test_remove_task_success() {
     // Arrange
     let mut to_do_list = ToDoList::default();
     to_do_list.add_task("To be deleted".to_string(), Importance::Normal); // ID will be 1
-    let args = RmArgs { id: 1 };
+    let args = RmArgs { tasks: vec![1.into()], ..Default::default() };
 
     // Act
-    let result = commands::remove_task(&mut to_do_list, &args);
+    let result = commands::remove_task(&mut to_do_list, &args, &Config::default());
 
     // Assert
     assert!(result.is_ok());
-    assert_eq!(result.unwrap(), "Successfully removed task with ID: 1");
+    assert_eq!(result.unwrap(), RemoveOutcome { tasks: vec![TaskRef { id: 1, name: "To be deleted".to_string() }] });
     assert!(to_do_list.is_empty());
 }
 
@@ -108,14 +126,15 @@ fn test_remove_task_success() {
 fn test_remove_task_not_found() {
     // Arrange
     let mut to_do_list = ToDoList::default();
-    let args = RmArgs { id: 99 };
+    let args = RmArgs { tasks: vec![99.into()], ..Default::default() };
 
     // Act
-    let result = commands::remove_task(&mut to_do_list, &args);
+    let result = commands::remove_task(&mut to_do_list, &args, &Config::default());
 
     // Assert
-    assert!(result.is_err());
-    assert_eq!(result.err().unwrap(), "Task with ID 99 not found.");
+    let error = result.err().unwrap();
+    assert!(matches!(error, Error::NotFound(99)));
+    assert_eq!(error.to_string(), "Task with ID 99 not found.");
 }
 
 // Test 7: Show tasks when the list is empty
@@ -125,11 +144,11 @@ fn test_show_tasks_empty() {
     let to_do_list = ToDoList::default();
 
     // Act
-    let result = commands::show_tasks(&to_do_list);
+    let result = commands::show_tasks(&to_do_list, &ShowArgs::default(), &Config::default());
 
     // Assert
     assert!(result.is_ok());
-    assert_eq!(result.unwrap(), "No tasks to show.");
+    assert_eq!(result.unwrap().render(&Config::default()), "No tasks to show.");
 }
 
 // Test 8: Show tasks when the list has items
@@ -141,12 +160,12 @@ fn test_show_tasks_with_items() {
     to_do_list.add_task("Second task".to_string(), Importance::Urgent);
 
     // Act
-    let result = commands::show_tasks(&to_do_list);
+    let result = commands::show_tasks(&to_do_list, &ShowArgs::default(), &Config::default());
     let expected_output = "There are 2 Tasks:\n- [id: 1] First task (Status: pending, Importance: normal)\n- [id: 2] Second task (Status: pending, Importance: urgent)";
 
     // Assert
     assert!(result.is_ok());
-    let output = result.unwrap();
+    let output = result.unwrap().render(&Config::default());
     // Normalize line endings for cross-platform compatibility
     let normalized_output = output.replace("\r\n", "\n");
     assert_eq!(normalized_output, expected_output.replace("\r\n", "\n"));
@@ -159,17 +178,19 @@ fn test_update_task_invalid_status() {
     let mut to_do_list = ToDoList::default();
     to_do_list.add_task("Initial Task".to_string(), Importance::Normal);
     let args = UpdateArgs {
-        id: 1,
+        tasks: vec![1.into()],
         status: Some("in-progress".to_string()),
         importance: None,
+        ..Default::default()
     };
 
     // Act
-    let result = commands::update_task(&mut to_do_list, &args);
+    let result = commands::update_task(&mut to_do_list, &args, &Config::default());
 
     // Assert
-    assert!(result.is_err());
-    assert_eq!(result.err().unwrap(), "Invalid status provided. Use 'pending' or 'finished'.");
+    let error = result.err().unwrap();
+    assert!(matches!(error, Error::InvalidStatus(ref value) if value == "in-progress"));
+    assert_eq!(error.to_string(), "'in-progress' is not a valid status. Use 'pending', 'started', or 'finished'.");
 }
 
 // Test 10: Fail to update a task due to invalid importance
@@ -179,17 +200,19 @@ fn test_update_task_invalid_importance() {
     let mut to_do_list = ToDoList::default();
     to_do_list.add_task("Initial Task".to_string(), Importance::Normal);
     let args = UpdateArgs {
-        id: 1,
+        tasks: vec![1.into()],
         status: None,
         importance: Some("low".to_string()),
+        ..Default::default()
     };
 
     // Act
-    let result = commands::update_task(&mut to_do_list, &args);
+    let result = commands::update_task(&mut to_do_list, &args, &Config::default());
 
     // Assert
-    assert!(result.is_err());
-    assert_eq!(result.err().unwrap(), "Invalid importance provided. Use 'normal', 'important', or 'urgent'.");
+    let error = result.err().unwrap();
+    assert!(matches!(error, Error::InvalidImportance(ref value) if value == "low"));
+    assert_eq!(error.to_string(), "'low' is not a valid importance. Use 'normal', 'important', or 'urgent'.");
 }
 
 
@@ -211,11 +234,16 @@ fn test_check_tasks_updates_task() {
     to_do_list.add_task("feat: Implement the new parser".to_string(), Importance::Important);
 
     // Act
-    let result = commands::check_tasks(&mut to_do_list, temp_dir.path());
+    let result = commands::check_tasks(&mut to_do_list, &mut ToDoList::default(), temp_dir.path(), &Config::default(), &mut None);
 
     // Assert
     assert!(result.is_ok());
-    assert_eq!(result.unwrap(), "Tasks updated successfully based on git logs.");
+    let outcome = result.unwrap();
+    assert_eq!(outcome.finished, vec![TaskRef { id: 1, name: "feat: Implement the new parser".to_string() }]);
+    assert_eq!(
+        outcome.render(&Config::default()),
+        "Task 'feat: Implement the new parser' finished!\nTasks updated successfully based on git logs."
+    );
     assert_eq!(to_do_list.tasks()[0].status, Status::Finished);
 }
 
@@ -228,11 +256,11 @@ fn test_check_tasks_no_updates() {
     to_do_list.add_task("A completely different task".to_string(), Importance::Normal);
 
     // Act
-    let result = commands::check_tasks(&mut to_do_list, temp_dir.path());
+    let result = commands::check_tasks(&mut to_do_list, &mut ToDoList::default(), temp_dir.path(), &Config::default(), &mut None);
 
     // Assert
     assert!(result.is_ok());
-    assert_eq!(result.unwrap(), "No tasks to update from git logs.");
+    assert_eq!(result.unwrap().render(&Config::default()), "No tasks to update from git logs.");
     assert_eq!(to_do_list.tasks()[0].status, Status::Pending);
 }
 
@@ -245,9 +273,648 @@ fn test_check_tasks_no_repo() {
     to_do_list.add_task("Some task".to_string(), Importance::Normal);
 
     // Act
-    let result = commands::check_tasks(&mut to_do_list, temp_dir.path());
+    let result = commands::check_tasks(&mut to_do_list, &mut ToDoList::default(), temp_dir.path(), &Config::default(), &mut None);
 
     // Assert
     assert!(result.is_ok());
-    assert_eq!(result.unwrap(), "No git repository found, can't check logs.");
+    assert_eq!(
+        result.unwrap(),
+        CheckOutcome { repository_found: false, started: Vec::new(), finished: Vec::new(), archived: Vec::new() }
+    );
+}
+
+// Test 14: hooks install writes all managed hooks into .git/hooks
+#[test]
+fn test_manage_hooks_install_and_status() {
+    // Arrange
+    let temp_dir = setup_test_repo("");
+    let to_do_list = ToDoList::default();
+    let install = HooksArgs { action: HooksAction::Install };
+    let status = HooksArgs { action: HooksAction::Status };
+
+    // Act
+    commands::manage_hooks(&to_do_list, temp_dir.path(), &install, &Config::default()).unwrap();
+    let result = commands::manage_hooks(&to_do_list, temp_dir.path(), &status, &Config::default());
+
+    // Assert
+    let expected = Hook::ALL.iter().map(|hook| HookStatus { hook: *hook, state: HookState::Installed }).collect();
+    assert_eq!(result.unwrap(), HooksOutcome::States { hooks: expected });
+    assert!(temp_dir.path().join(".git").join("hooks").join("commit-msg").exists());
+}
+
+// Test 15: commit-msg validation warns about unknown task IDs only
+#[test]
+fn test_check_commit_message_unknown_id() {
+    // Arrange
+    let mut to_do_list = ToDoList::default();
+    to_do_list.add_task("Known task".to_string(), Importance::Normal); // ID will be 1
+
+    // Act
+    let result = commands::check_commit_message(&to_do_list, "feat: Known task\n\nCloses #1, refs #7\n");
+
+    // Assert
+    assert_eq!(result, vec![7]);
+    assert_eq!(
+        HooksOutcome::MessageChecked { unknown: result }.render(&Config::default()),
+        "Warning: commit message references unknown task #7."
+    );
+}
+
+// Test 16: commit-msg builds a subject from task names and Closes trailers
+#[test]
+fn test_commit_message_for_tasks() {
+    // Arrange
+    let mut to_do_list = ToDoList::default();
+    to_do_list.add_task("feat: Parse config".to_string(), Importance::Normal);
+    to_do_list.add_task("docs: Update README".to_string(), Importance::Normal);
+    let args = CommitMsgArgs { tasks: vec![1.into(), 2.into()] };
+
+    // Act
+    let result = commands::commit_message(&to_do_list, &args);
+
+    // Assert
+    assert_eq!(result.unwrap().message, "feat: Parse config; docs: Update README\n\nCloses #1\nCloses #2");
+}
+
+// Test 17: commit-msg fails on an unknown task
+#[test]
+fn test_commit_message_unknown_task() {
+    // Arrange
+    let to_do_list = ToDoList::default();
+    let args = CommitMsgArgs { tasks: vec![4.into()] };
+
+    // Act
+    let result = commands::commit_message(&to_do_list, &args);
+
+    // Assert
+    assert!(matches!(result, Err(Error::NotFound(4))));
+}
+
+// Test 18: prepare-commit-msg pre-fills the branch's task, or the started ones, and lists the other open tasks as comments
+#[test]
+fn test_prepare_commit_message_prefills_tasks() {
+    // Arrange
+    let mut to_do_list = ToDoList::default();
+    to_do_list.add_task("Pending one".to_string(), Importance::Normal);
+    to_do_list.add_task("Done one".to_string(), Importance::Normal);
+    to_do_list.add_task("Started one".to_string(), Importance::Normal);
+    to_do_list.update_task(2, Some(Status::Finished), None).unwrap();
+    to_do_list.update_task(3, Some(Status::Started), None).unwrap();
+    let template = "\n; Please enter the commit message for your changes.\n";
+
+    // Act
+    let on_branch = commands::prepare_commit_message(&to_do_list, template, Some(1), ';');
+    let started = commands::prepare_commit_message(&to_do_list, template, None, ';');
+
+    // Assert
+    assert!(on_branch.starts_with("Pending one\n\nCloses #1\n"));
+    assert!(on_branch.contains("\n;   #3 Started one\n"));
+    assert!(started.starts_with("Started one\n\nCloses #3\n"));
+    assert!(!started.contains("Done one"));
+    assert!(started.ends_with(template));
+}
+
+// Test 19: check starts tasks with commits on their branch and finishes them once merged
+#[test]
+fn test_check_tasks_follows_task_branches() {
+    // Arrange
+    let temp_dir = setup_test_repo(
+        "0 1 Dev <d@x> 1 +0000\tcheckout: moving from main to task/1-parser\n\
+         1 2 Dev <d@x> 2 +0000\tcommit: wip\n\
+         2 3 Dev <d@x> 3 +0000\tcheckout: moving from task/1-parser to task/2-docs\n\
+         3 4 Dev <d@x> 4 +0000\tcommit: more wip\n\
+         4 5 Dev <d@x> 5 +0000\tcheckout: moving from task/2-docs to main\n\
+         5 6 Dev <d@x> 6 +0000\tmerge task/1-parser: Merge made by the 'ort' strategy.\n",
+    );
+    let mut to_do_list = ToDoList::default();
+    to_do_list.add_task("Parser".to_string(), Importance::Normal);
+    to_do_list.add_task("Docs".to_string(), Importance::Normal);
+
+    // Act
+    let result = commands::check_tasks(&mut to_do_list, &mut ToDoList::default(), temp_dir.path(), &Config::default(), &mut None);
+
+    // Assert
+    let outcome = result.unwrap();
+    let started: Vec<u32> = outcome.started.iter().map(|task| task.id).collect();
+    assert_eq!(started, vec![1, 2]);
+    assert_eq!(outcome.finished, vec![TaskRef { id: 1, name: "Parser".to_string() }]);
+    assert_eq!(to_do_list.tasks()[0].status, Status::Finished);
+    assert_eq!(to_do_list.tasks()[1].status, Status::Started);
+}
+
+// Test 20: start outside a git repository only updates the status
+#[test]
+fn test_start_task_without_repo() {
+    // Arrange
+    let temp_dir = tempfile::Builder::new().prefix("rudden-no-repo-").tempdir().unwrap();
+    let mut to_do_list = ToDoList::default();
+    to_do_list.add_task("Some task".to_string(), Importance::Normal);
+    let args = StartArgs { task: 1.into(), no_branch: false, branch: BranchArgs::default() };
+
+    // Act
+    let result = commands::start_task(&mut to_do_list, temp_dir.path(), &args, &Config::default());
+
+    // Assert
+    assert_eq!(result.unwrap(), StartOutcome { id: 1, branch: None });
+    assert_eq!(to_do_list.tasks()[0].status, Status::Started);
+}
+
+// Test 21: init creates the store once and reports an existing one afterwards
+#[test]
+fn test_init_store() {
+    // Arrange
+    let temp_dir = tempfile::Builder::new().prefix("rudden-init-").tempdir().unwrap();
+    let store = Store::locate(temp_dir.path(), None);
+
+    // Act
+    let first = commands::init_store(&store).unwrap();
+    let second = commands::init_store(&store).unwrap();
+
+    // Assert
+    assert!(store.exists());
+    assert_eq!(first, InitOutcome { dir: store.dir.clone(), created: true });
+    assert!(!second.created);
+    assert!(second.render(&Config::default()).starts_with("Rudden store already exists at"));
+}
+
+// Test 22: show --all merges both lists and labels each task with its origin
+#[test]
+fn test_show_all_tasks_labels_origin() {
+    // Arrange
+    let mut repo_list = ToDoList::default();
+    repo_list.add_task("Fix build".to_string(), Importance::Urgent);
+    let mut global_list = ToDoList::default();
+    global_list.add_task("Buy milk".to_string(), Importance::Normal);
+
+    // Act
+    let result = commands::show_all_tasks(&repo_list, &global_list, &ShowArgs::default(), &Config::default());
+
+    // Assert
+    assert_eq!(
+        result.unwrap().render(&Config::default()),
+        "There are 2 Tasks:\n- [repo] [id: 1] Fix build (Status: pending, Importance: urgent)\n- [global] [id: 1] Buy milk (Status: pending, Importance: normal)"
+    );
+}
+
+// Test 23: dash counts tasks per repository and ranks open tasks by importance
+#[test]
+fn test_dashboard_aggregates_repositories() {
+    // Arrange
+    let mut api = ToDoList::default();
+    api.add_task("Fix login".to_string(), Importance::Urgent);
+    api.add_task("Write docs".to_string(), Importance::Normal);
+    api.update_task(2, Some(Status::Finished), None).unwrap();
+    let mut web = ToDoList::default();
+    web.add_task("Polish CSS".to_string(), Importance::Important);
+    let lists = vec![("api".to_string(), api), ("web".to_string(), web)];
+
+    // Act
+    let result = commands::dashboard(&lists, &DashArgs { top: 5 });
+
+    // Assert
+    assert_eq!(
+        result.unwrap().render(&Config::default()),
+        "Repositories:\n\
+        - api: 1 pending, 0 started, 1 finished (normal: 1, important: 0, urgent: 1)\n\
+        - web: 1 pending, 0 started, 0 finished (normal: 0, important: 1, urgent: 0)\n\
+        \n\
+        Top open tasks:\n\
+        - [api id: 1] Fix login (Status: pending, Importance: urgent)\n\
+        - [web id: 1] Polish CSS (Status: pending, Importance: important)"
+    );
+}
+
+// Test 24: add falls back to the configured default importance
+#[test]
+fn test_add_task_uses_configured_default_importance() {
+    // Arrange
+    let mut to_do_list = ToDoList::default();
+    let mut config = Config::default();
+    config.set_cli("add.default_importance", "important").unwrap();
+    let args = AddArgs { message: "Configured".to_string(), importance: None, ..Default::default() };
+
+    // Act
+    let result = commands::add_task(&mut to_do_list, &args, &config);
+
+    // Assert
+    assert!(result.is_ok());
+    assert_eq!(to_do_list.tasks()[0].importance, Importance::Important);
+}
+
+// Test 25: show uses the configured output templates
+#[test]
+fn test_show_tasks_with_configured_templates() {
+    // Arrange
+    let mut to_do_list = ToDoList::default();
+    to_do_list.add_task("Templated".to_string(), Importance::Urgent);
+    let mut config = Config::default();
+    config.set_cli("show.header", "{count} task(s)").unwrap();
+    config.set_cli("show.line", "#{id} {name} [{importance}]").unwrap();
+
+    // Act
+    let result = commands::show_tasks(&to_do_list, &ShowArgs::default(), &Config::default());
+
+    // Assert
+    assert_eq!(result.unwrap().render(&config), "1 task(s)\n#1 Templated [urgent]");
+}
+
+// Test 26: config set refuses to write a repo config without a store
+#[test]
+fn test_manage_config_set_requires_store() {
+    // Arrange
+    let temp_dir = tempfile::Builder::new().prefix("rudden-config-").tempdir().unwrap();
+    let repo_config = temp_dir.path().join(".rudden").join("config.toml");
+    let args = ConfigArgs {
+        action: ConfigAction::Set { key: "branch.main".to_string(), value: "develop".to_string(), user: false },
+    };
+
+    // Act
+    let result = commands::manage_config(&Config::default(), &args, &repo_config, None);
+
+    // Assert
+    assert!(matches!(result, Err(Error::StoreNotFound)));
+}
+
+// Test 27: add and update tag tasks, rejecting malformed tags
+#[test]
+fn test_add_and_update_tags() {
+    // Arrange
+    let mut to_do_list = ToDoList::default();
+    let add = AddArgs { message: "Tagged".to_string(), tags: vec!["api".to_string(), "ui".to_string()], ..Default::default() };
+    let untag = UpdateArgs { tasks: vec![1.into()], untags: vec!["ui".to_string()], ..Default::default() };
+    let bad = UpdateArgs { tasks: vec![1.into()], tags: vec!["a,b".to_string()], ..Default::default() };
+
+    // Act
+    commands::add_task(&mut to_do_list, &add, &Config::default()).unwrap();
+    commands::update_task(&mut to_do_list, &untag, &Config::default()).unwrap();
+    let result = commands::update_task(&mut to_do_list, &bad, &Config::default());
+
+    // Assert
+    assert_eq!(to_do_list.tasks()[0].tags, vec!["api".to_string()]);
+    assert!(to_do_list.tasks()[0].created.is_some());
+    assert!(matches!(result, Err(Error::InvalidTag(_))));
+}
+
+// Test 28: show filters by a query and expands saved @queries
+#[test]
+fn test_show_tasks_filters_by_query() {
+    // Arrange
+    let mut to_do_list = ToDoList::default();
+    let api = AddArgs { message: "Fix API".to_string(), importance: Some("urgent".to_string()), tags: vec!["api".to_string()], ..Default::default() };
+    let docs = AddArgs { message: "Write docs".to_string(), tags: vec!["docs".to_string()], ..Default::default() };
+    commands::add_task(&mut to_do_list, &api, &Config::default()).unwrap();
+    commands::add_task(&mut to_do_list, &docs, &Config::default()).unwrap();
+    let mut config = Config::default();
+    config.set_cli("query.hot", "importance>=important -status:finished").unwrap();
+    let by_tag = ShowArgs { query: vec!["tag:docs".to_string()], ..Default::default() };
+    let saved = ShowArgs { query: vec!["@hot".to_string()], ..Default::default() };
+    let missing = ShowArgs { query: vec!["@nope".to_string()], ..Default::default() };
+
+    // Act
+    let by_tag = commands::show_tasks(&to_do_list, &by_tag, &config).unwrap();
+    let saved = commands::show_tasks(&to_do_list, &saved, &config).unwrap();
+    let missing = commands::show_tasks(&to_do_list, &missing, &config);
+
+    // Assert
+    let ids = |outcome: &rudden::outcome::ShowOutcome| outcome.tasks.iter().map(|listed| listed.task.id).collect::<Vec<_>>();
+    assert_eq!(ids(&by_tag), vec![2]);
+    assert_eq!(ids(&saved), vec![1]);
+    assert!(matches!(missing, Err(Error::InvalidQuery(_))));
+}
+
+// Test 29: search matches descriptions and tags, ignoring accents
+#[test]
+fn test_search_tasks_with_description() {
+    // Arrange
+    let mut to_do_list = ToDoList::default();
+    let add = AddArgs { message: "Update menu".to_string(), description: Some("Add the crème brûlée".to_string()), ..Default::default() };
+    commands::add_task(&mut to_do_list, &add, &Config::default()).unwrap();
+    to_do_list.add_task("Unrelated".to_string(), Importance::Normal);
+    let args = SearchArgs { terms: vec!["creme brulee".to_string()], ..Default::default() };
+
+    // Act
+    let outcome = commands::search_tasks(&to_do_list, &args).unwrap();
+
+    // Assert
+    assert_eq!(
+        outcome.render(&Config::default()),
+        "Found 1 matching task:\n- [id: 1] Update menu (Status: pending, Importance: normal)\n    Add the crème brûlée"
+    );
+}
+
+// Test 30: Commands accept name selectors and report ambiguous ones
+#[test]
+fn test_commands_accept_name_selectors() {
+    // Arrange
+    let mut to_do_list = ToDoList::default();
+    to_do_list.add_task("Fix login".to_string(), Importance::Normal);
+    to_do_list.add_task("Login page".to_string(), Importance::Normal);
+    to_do_list.add_task("Release".to_string(), Importance::Normal);
+    let update = UpdateArgs { tasks: vec!["page".parse().unwrap()], status: Some("finished".to_string()), ..Default::default() };
+    let ambiguous = RmArgs { tasks: vec!["login".parse().unwrap()], ..Default::default() };
+    let last = RmArgs { tasks: vec!["last".parse().unwrap()], ..Default::default() };
+
+    // Act
+    let updated = commands::update_task(&mut to_do_list, &update, &Config::default()).unwrap();
+    let error = commands::remove_task(&mut to_do_list, &ambiguous, &Config::default()).err().unwrap();
+    let removed = commands::remove_task(&mut to_do_list, &last, &Config::default()).unwrap();
+
+    // Assert
+    assert_eq!(updated.tasks, vec![TaskRef { id: 2, name: "Login page".to_string() }]);
+    assert_eq!(error.to_string(), "'login' matches several tasks: #1 Fix login, #2 Login page. Use an ID instead.");
+    assert_eq!(removed.tasks, vec![TaskRef { id: 3, name: "Release".to_string() }]);
+}
+
+// Test 31: Bulk update and rm take ranges and --where, and big removals need --yes
+#[test]
+fn test_bulk_update_and_remove() {
+    // Arrange
+    let mut to_do_list = ToDoList::default();
+    for name in ["One", "Two", "Three", "Four"] {
+        to_do_list.add_task(name.to_string(), Importance::Normal);
+    }
+    let mut config = Config::default();
+    config.set_cli("rm.confirm_above", "2").unwrap();
+    let finish = UpdateArgs { tasks: vec!["1-3".parse().unwrap()], status: Some("finished".to_string()), ..Default::default() };
+    let unconfirmed = RmArgs { filter: Some("status:finished".to_string()), ..Default::default() };
+    let confirmed = RmArgs { filter: Some("status:finished".to_string()), yes: true, ..Default::default() };
+
+    // Act
+    let updated = commands::update_task(&mut to_do_list, &finish, &config).unwrap();
+    let refused = commands::remove_task(&mut to_do_list, &unconfirmed, &config);
+    let removed = commands::remove_task(&mut to_do_list, &confirmed, &config).unwrap();
+
+    // Assert
+    assert_eq!(updated.ids(), vec![1, 2, 3]);
+    assert!(matches!(refused, Err(Error::NeedsConfirmation(3))));
+    assert_eq!(
+        removed.render(&config),
+        "Successfully removed 3 tasks:\n- [id: 1] One\n- [id: 2] Two\n- [id: 3] Three"
+    );
+    assert_eq!(to_do_list.tasks().len(), 1);
+}
+
+// Test 32: Archiving moves finished tasks out of the list, and unarchiving brings them back
+#[test]
+fn test_archive_and_unarchive() {
+    // Arrange
+    let mut to_do_list = ToDoList::default();
+    let mut archive = ToDoList::default();
+    to_do_list.add_task("Done".to_string(), Importance::Normal);
+    to_do_list.add_task("Pending".to_string(), Importance::Normal);
+    to_do_list.update_task(1, Some(Status::Finished), None).unwrap();
+    let recent = ArchiveArgs { older_than: Some(3600) };
+    let unarchive = UnarchiveArgs { tasks: vec!["done".parse().unwrap()] };
+
+    // Act
+    let skipped = commands::archive_tasks(&mut to_do_list, &mut archive, &recent).unwrap();
+    let archived = commands::archive_tasks(&mut to_do_list, &mut archive, &ArchiveArgs::default()).unwrap();
+    let shown = commands::show_tasks(&archive, &ShowArgs::default(), &Config::default()).unwrap();
+    let restored = commands::unarchive_tasks(&mut to_do_list, &mut archive, &unarchive).unwrap();
+
+    // Assert
+    assert_eq!(skipped.render(&Config::default()), "No finished tasks to archive.");
+    assert_eq!(archived.render(&Config::default()), "Successfully archived task with ID: 1");
+    assert_eq!(shown.ids(), vec![1]);
+    assert_eq!(restored.tasks, vec![TaskRef { id: 1, name: "Done".to_string() }]);
+    assert!(archive.tasks().is_empty());
+    assert_eq!(to_do_list.tasks().len(), 2);
+}
+
+// Test 33: Import adds new tasks with their subtasks and skips names already in the list
+#[test]
+fn test_import_skips_existing_tasks() {
+    // Arrange
+    let temp_dir = tempfile::tempdir().unwrap();
+    let file = temp_dir.path().join("backlog.md");
+    fs::write(&file, "- [ ] Release 2.0\n  - [x] write notes\n- [ ] Fix login #auth\n").unwrap();
+    let mut to_do_list = ToDoList::default();
+    to_do_list.add_task("Write notes".to_string(), Importance::Normal);
+    let args = ImportArgs { file, from: ImportFormat::Markdown };
+
+    // Act
+    let first = commands::import_tasks(&mut to_do_list, &args).unwrap();
+    let second = commands::import_tasks(&mut to_do_list, &args).unwrap();
+
+    // Assert
+    assert_eq!(
+        first.render(&Config::default()),
+        "Imported 2 tasks:\n- [id: 2] Release 2.0\n- [id: 3] Fix login\nSkipped 1 task already in the list:\n- [id: 1] Write notes"
+    );
+    assert!(second.created.is_empty());
+    assert_eq!(second.skipped.len(), 3);
+    assert_eq!(to_do_list.get(3).unwrap().tags, vec!["auth"]);
+    assert_eq!(to_do_list.get(1).unwrap().parent, None);
+}
+
+// Test 34: Scan creates tasks for TODO comments, follows them as they move and finishes them when they go
+#[test]
+fn test_scan_tracks_todo_comments() {
+    // Arrange
+    let repo = tempfile::tempdir().unwrap();
+    let file = repo.path().join("main.rs");
+    fs::write(&file, "// TODO: handle errors\nfn main() {}\n// FIXME: slow\n").unwrap();
+    let mut to_do_list = ToDoList::default();
+    let mut config = Config::default();
+    config.set_cli("add.default_importance", "important").unwrap();
+
+    // Act
+    let first = commands::scan_tasks(&mut to_do_list, repo.path(), &ScanArgs::default(), &config).unwrap();
+    fs::write(&file, "\n// TODO: handle errors\nfn main() {}\n").unwrap();
+    let second = commands::scan_tasks(&mut to_do_list, repo.path(), &ScanArgs::default(), &config).unwrap();
+    let third = commands::scan_tasks(&mut to_do_list, repo.path(), &ScanArgs::default(), &config).unwrap();
+
+    // Assert
+    assert_eq!(first.ids(), vec![1, 2]);
+    assert_eq!(to_do_list.get(1).unwrap().importance, Importance::Important);
+    assert_eq!(to_do_list.get(2).unwrap().tags, vec!["fixme"]);
+    assert_eq!(
+        second.render(&Config::default()),
+        "Found 1 TODO comment.\nUpdated 1 task:\n- [id: 1] handle errors\nFinished 1 task:\n- [id: 2] slow"
+    );
+    assert_eq!(to_do_list.get(1).unwrap().location.as_ref().unwrap().to_string(), "main.rs:2");
+    assert_eq!(third.render(&Config::default()), "Found 1 TODO comment.");
+}
+
+/// Starts `rudden serve` on a free port for a new store, answering requests on a background thread.
+fn start_server(token: Option<&str>) -> (tempfile::TempDir, Store, SocketAddr) {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let store = Store { dir: temp_dir.path().join(".rudden") };
+    fs::create_dir(&store.dir).unwrap();
+    let args = ServeArgs { port: 0, host: "127.0.0.1".to_string(), token: token.map(str::to_string) };
+    let api = Api::bind(&args, store.clone(), temp_dir.path().to_path_buf(), Config::default()).unwrap();
+    let addr = api.local_addr().unwrap();
+    std::thread::spawn(move || api.run());
+    (temp_dir, store, addr)
+}
+
+/// Header for requests with a JSON body.
+const JSON: &str = "Content-Type: application/json\r\n";
+
+/// Sends one HTTP request and returns the response's status and body. It goes to
+/// `Host: localhost` unless `headers` names another host.
+fn request(addr: SocketAddr, method: &str, path: &str, headers: &str, body: &str) -> (u16, serde_json::Value) {
+    let mut stream = TcpStream::connect(addr).unwrap();
+    let host = if headers.contains("Host: ") { "" } else { "Host: localhost\r\n" };
+    write!(
+        stream,
+        "{} {} HTTP/1.1\r\n{}Connection: close\r\n{}Content-Length: {}\r\n\r\n{}",
+        method, path, host, headers, body.len(), body
+    )
+    .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    let status = head.split(' ').nth(1).unwrap().parse().unwrap();
+    (status, serde_json::from_str(body).unwrap())
+}
+
+// Test 35: The HTTP API creates, lists, updates and removes tasks in the store
+#[test]
+fn test_serve_task_endpoints() {
+    // Arrange
+    let (_temp_dir, store, addr) = start_server(None);
+
+    // Act
+    let created = request(addr, "POST", "/tasks", JSON, r#"{"name": "Fix login", "importance": "urgent", "tags": ["api"]}"#);
+    request(addr, "POST", "/tasks", JSON, r#"{"name": "Write docs"}"#);
+    let listed = request(addr, "GET", "/tasks?q=tag%3Aapi", "", "");
+    let updated = request(addr, "PATCH", "/tasks/login", JSON, r#"{"status": "started"}"#);
+    let missing = request(addr, "DELETE", "/tasks/9", "", "");
+    let range = request(addr, "DELETE", "/tasks/1-2", "", "");
+    let named = request(addr, "DELETE", "/tasks/docs", "", "");
+    let invalid = request(addr, "POST", "/tasks", JSON, r#"{"title": "Fix login"}"#);
+    let removed = request(addr, "DELETE", "/tasks/2", "", "");
+    let checked = request(addr, "POST", "/check", "", "");
+
+    // Assert
+    assert_eq!(created, (201, serde_json::json!({ "id": 1 })));
+    assert_eq!(listed.0, 200);
+    assert_eq!(listed.1["tasks"].as_array().unwrap().len(), 1);
+    assert_eq!(listed.1["tasks"][0]["name"], "Fix login");
+    assert_eq!(updated.0, 200);
+    assert_eq!(missing.0, 404);
+    assert_eq!(range.0, 400);
+    assert_eq!(named.0, 400);
+    assert_eq!(invalid.0, 400);
+    assert_eq!(removed.1["tasks"][0]["name"], "Write docs");
+    assert_eq!(checked.1["repository_found"], false);
+    let to_do_list = ToDoList::load(store.tasks_path()).unwrap();
+    assert_eq!(to_do_list.tasks().len(), 1);
+    assert_eq!(to_do_list.get(1).unwrap().status, Status::Started);
+}
+
+// Test 36: With a token set, the HTTP API refuses requests that don't send it
+#[test]
+fn test_serve_requires_token() {
+    // Arrange
+    let (_temp_dir, _store, addr) = start_server(Some("s3cret"));
+
+    // Act
+    let without = request(addr, "GET", "/tasks", "", "");
+    let wrong = request(addr, "GET", "/tasks", "Authorization: Bearer guess\r\n", "");
+    let with = request(addr, "GET", "/tasks", "Authorization: Bearer s3cret\r\n", "");
+    let unknown = request(addr, "GET", "/projects", "Authorization: Bearer s3cret\r\n", "");
+
+    // Assert
+    assert_eq!(without.0, 401);
+    assert_eq!(wrong.0, 401);
+    assert_eq!(with, (200, serde_json::json!({ "tasks": [] })));
+    assert_eq!(unknown.0, 404);
+}
+
+// Test 37: check only looks at reflog entries after the last one it processed
+#[test]
+fn test_check_tasks_skips_processed_entries() {
+    // Arrange
+    let temp_dir = setup_test_repo("0 1 Dev <d@x> 1 +0000\tcommit: Fix login\n");
+    let mut to_do_list = ToDoList::default();
+    to_do_list.add_task("Fix login".to_string(), Importance::Normal);
+    let mut last_checked = None;
+    commands::check_tasks(&mut to_do_list, &mut ToDoList::default(), temp_dir.path(), &Config::default(), &mut last_checked).unwrap();
+    to_do_list.update_task(1, Some(Status::Pending), None).unwrap();
+
+    // Act
+    let result = commands::check_tasks(&mut to_do_list, &mut ToDoList::default(), temp_dir.path(), &Config::default(), &mut last_checked);
+
+    // Assert
+    assert!(result.unwrap().finished.is_empty());
+    assert_eq!(last_checked.as_deref(), Some("0 1 Dev <d@x> 1 +0000\tcommit: Fix login"));
+    assert_eq!(to_do_list.tasks()[0].status, Status::Pending);
+}
+
+// Test 38: The hooks skip repositories without a store instead of failing
+#[test]
+fn test_check_skips_without_store() {
+    // Arrange
+    let temp_dir = setup_test_repo("");
+    let store = temp_dir.path().join(".rudden");
+    let message = temp_dir.path().join("COMMIT_EDITMSG");
+    fs::write(&message, "fix: parser\n\nCloses #7\n").unwrap();
+    let cli = |args: &[&str]| Cli::parse_from(["rudden", "--store", store.to_str().unwrap()].iter().chain(args));
+    let mut session = Session::open(&cli(&["check"])).unwrap();
+
+    // Act
+    let skipped = session.execute(cli(&["check", "--skip-without-store"]));
+    let checked = session.execute(cli(&["hooks", "check-msg", message.to_str().unwrap()]));
+    let prepared = session.execute(cli(&["hooks", "prepare-msg", message.to_str().unwrap()]));
+    let failed = session.execute(cli(&["check"]));
+
+    // Assert
+    assert_eq!(skipped.unwrap(), "");
+    assert_eq!(checked.unwrap(), "");
+    assert_eq!(prepared.unwrap(), "");
+    assert_eq!(fs::read_to_string(&message).unwrap(), "fix: parser\n\nCloses #7\n");
+    assert!(matches!(failed, Err(Error::StoreNotFound)));
+    assert!(!store.exists());
+}
+
+// Test 39: check finishes tasks closed by a `Closes #<id>` line in a commit message
+#[test]
+fn test_check_tasks_reads_closes_lines() {
+    // Arrange
+    let temp_dir = tempfile::Builder::new().prefix("rudden-closes-").tempdir().unwrap();
+    let git = |args: &[&str]| {
+        let status = std::process::Command::new("git")
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .current_dir(temp_dir.path())
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success());
+    };
+    git(&["init", "--quiet"]);
+    git(&["commit", "--quiet", "--allow-empty", "-m", "fix: the parser", "-m", "Closes #2"]);
+    let mut to_do_list = ToDoList::default();
+    to_do_list.add_task("Write the parser".to_string(), Importance::Normal);
+    to_do_list.add_task("Fix the parser".to_string(), Importance::Normal);
+
+    // Act
+    let outcome = commands::check_tasks(&mut to_do_list, &mut ToDoList::default(), temp_dir.path(), &Config::default(), &mut None).unwrap();
+
+    // Assert
+    assert_eq!(outcome.finished, vec![TaskRef { id: 2, name: "Fix the parser".to_string() }]);
+    assert_eq!(to_do_list.tasks()[0].status, Status::Pending);
+}
+
+// Test 40: The HTTP API refuses other hosts' names and bodies that aren't sent as JSON
+#[test]
+fn test_serve_checks_host_and_content_type() {
+    // Arrange
+    let (_temp_dir, store, addr) = start_server(None);
+    let own_host = format!("Host: {}\r\n", addr);
+
+    // Act
+    let rebound = request(addr, "GET", "/tasks", "Host: tasks.evil.example\r\n", "");
+    let by_address = request(addr, "GET", "/tasks", &own_host, "");
+    let form = request(addr, "POST", "/tasks", "Content-Type: text/plain\r\n", r#"{"name": "Fix login"}"#);
+    let json = request(addr, "POST", "/tasks", "Content-Type: application/json; charset=utf-8\r\n", r#"{"name": "Fix login"}"#);
+
+    // Assert
+    assert_eq!(rebound.0, 403);
+    assert_eq!(by_address.0, 200);
+    assert_eq!(form.0, 415);
+    assert_eq!(json.0, 201);
+    assert_eq!(ToDoList::load(store.tasks_path()).unwrap().tasks().len(), 1);
 }